@@ -2,22 +2,29 @@ use std::sync::{Arc, RwLock, RwLockWriteGuard};
 
 use super::{UIMouseState, UIRenderMode};
 use crate::context::MemState;
+use crate::drag_drop::{DragPayload, DropEvent};
+use crate::gesture::Gesture;
+use crate::inspector::InspectorField;
 use crate::shape::ShapeTrait;
 use crate::traits::UIElement;
-use bevy::color::palettes::css::BLUE_VIOLET;
+use bevy::color::palettes::css::{BLUE_VIOLET, DODGER_BLUE, GOLD, GRAY, LIMEGREEN, ORANGE_RED};
+use bevy::color::Alpha;
+use bevy::input::{keyboard::KeyboardInput, mouse::MouseButton, ButtonState};
 use bevy::math::{Quat, Vec3, Vec4, VectorSpace};
+use bevy::window::CursorIcon;
 use bevy::{color::Srgba, math::Vec2};
 use bevy_vector_shapes::prelude::ShapePainter;
 use bevy_vector_shapes::shapes::RectPainter;
 use idgenerator::IdInstance;
 use taffy::prelude::auto;
-use taffy::Position;
 use taffy::{prelude::length, Dimension, Rect, Size, Style};
+use taffy::{LengthPercentage, LengthPercentageAuto, Line, MaxTrackSizingFunction, MinMax, MinTrackSizingFunction, TrackSizingFunction};
 
 #[derive(Clone, Debug)]
 pub enum ElementType {
     Content,
     Debug,
+    Inspector,
 }
 
 #[derive(Clone, Debug)]
@@ -40,10 +47,283 @@ pub enum AlignItems {
     NotSet,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlexWrap {
+    NoWrap,
+    Wrap,
+    WrapReverse,
+}
+
+/// A length in pixels or a fraction of the parent (0.5 is 50%).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    Auto,
+    Px(f32),
+    Percent(f32),
+}
+
+impl Length {
+    pub(crate) fn to_dimension(&self) -> Dimension {
+        match self {
+            Length::Auto => Dimension::Auto,
+            Length::Px(value) => Dimension::Length(*value),
+            Length::Percent(value) => Dimension::Percent(*value),
+        }
+    }
+
+    pub(crate) fn to_inset(&self) -> LengthPercentageAuto {
+        match self {
+            Length::Auto => LengthPercentageAuto::Auto,
+            Length::Px(value) => LengthPercentageAuto::Length(*value),
+            Length::Percent(value) => LengthPercentageAuto::Percent(*value),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Position {
+    Relative,
+    Absolute,
+}
+
+/// Point of the parent an absolute element is pinned to, with the same point of the element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Anchor point as a fraction of the size, (0, 0) is top left.
+    pub fn fraction(&self) -> Vec2 {
+        match self {
+            Anchor::TopLeft => Vec2::new(0., 0.),
+            Anchor::Top => Vec2::new(0.5, 0.),
+            Anchor::TopRight => Vec2::new(1., 0.),
+            Anchor::Left => Vec2::new(0., 0.5),
+            Anchor::Center => Vec2::new(0.5, 0.5),
+            Anchor::Right => Vec2::new(1., 0.5),
+            Anchor::BottomLeft => Vec2::new(0., 1.),
+            Anchor::Bottom => Vec2::new(0.5, 1.),
+            Anchor::BottomRight => Vec2::new(1., 1.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollAxes {
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl ScrollAxes {
+    pub fn horizontal(&self) -> bool {
+        *self != ScrollAxes::Vertical
+    }
+
+    pub fn vertical(&self) -> bool {
+        *self != ScrollAxes::Horizontal
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    Visible,
+    Hidden,
+    /// Hidden, following the rounded corners of the element.
+    ClipRounded,
+}
+
+/// Direction of each corner in painter space, in the order of the corner radii of
+/// bevy_vector_shapes: top right, bottom right, top left, bottom left.
+const CORNERS: [Vec2; 4] = [Vec2::new(1., 1.), Vec2::new(1., -1.), Vec2::new(-1., 1.), Vec2::new(-1., -1.)];
+
+/// Area the descendants of clipping elements are cut to, a rect with per corner radii
+/// in painter space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clip {
+    pub(crate) rect: bevy::math::Rect,
+    pub(crate) radii: Vec4,
+}
+
+impl Clip {
+    pub fn rect(&self) -> bevy::math::Rect {
+        self.rect
+    }
+
+    /// Radius of `corner`, no larger than half the rect.
+    fn radius(&self, corner: usize) -> f32 {
+        self.radii[corner].min(self.rect.half_size().min_element()).max(0.)
+    }
+
+    /// Smallest radius a rect corner at `point`, pointing like `corner`, needs to stay inside
+    /// the rounded corner of the clip. Its arc is then tangent to the arc of the clip.
+    fn corner_fit(&self, corner: usize, point: Vec2) -> f32 {
+        let radius = self.radius(corner);
+        let outer = self.rect.center() + CORNERS[corner] * self.rect.half_size();
+        let inset = ((outer - point) * CORNERS[corner]).max(Vec2::ZERO);
+        if inset.x >= radius || inset.y >= radius {
+            return 0.;
+        }
+        (radius - inset.x - inset.y - (2. * inset.x * inset.y).sqrt()).max(0.)
+    }
+
+    /// Corner radii for `rect`, a part of the clip rect, to follow the rounded corners of the clip.
+    pub fn fit(&self, rect: bevy::math::Rect) -> Vec4 {
+        let corner = |index: usize| self.corner_fit(index, rect.center() + CORNERS[index] * rect.half_size());
+        Vec4::new(corner(0), corner(1), corner(2), corner(3))
+    }
+
+    /// Nested clips, the corners of the common rect follow the rounded corners of both.
+    pub(crate) fn intersect(&self, other: Clip) -> Clip {
+        let rect = self.rect.intersect(other.rect);
+        let radii = self.fit(rect).max(other.fit(rect));
+        Clip { rect, radii }
+    }
+
+    /// Whether some of `rect` is inside the clip rect, shapes are hidden once it isn't.
+    pub fn overlaps(&self, rect: bevy::math::Rect) -> bool {
+        !self.rect.intersect(rect).is_empty()
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        if !self.rect.contains(point) {
+            return false;
+        }
+        let offset = point - self.rect.center();
+        let corner = match (offset.x >= 0., offset.y >= 0.) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        };
+        let radius = self.radius(corner);
+        let inside = offset.abs() - (self.rect.half_size() - radius);
+        inside.x <= 0. || inside.y <= 0. || inside.length() <= radius
+    }
+}
+
+/// Distance between an element and its focus ring.
+const FOCUS_RING_GAP: f32 = 2.;
+
+/// Space reserved by taffy for the scrollbars of a scroll view.
+pub(crate) const SCROLLBAR_WIDTH: f32 = 8.;
+const SCROLLBAR_MIN_THUMB: f32 = 16.;
+
+/// Distance in z between two elements following each other in the paint order.
+pub(crate) const DEPTH_STEP: f32 = 0.001;
+/// Height of the shape and the text of an element above its fills, below the next element.
+const SHAPE_LIFT: f32 = DEPTH_STEP / 4.;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Display {
+    Flex,
+    Grid,
+}
+
+/// Size of one grid track, percentages are fractions of the container (0.5 is 50%).
+#[derive(Clone, Debug, PartialEq)]
+pub enum GridTrack {
+    Length(f32),
+    Percent(f32),
+    Fr(f32),
+    Auto,
+    MinContent,
+    MaxContent,
+    MinMax(Box<GridTrack>, Box<GridTrack>),
+}
+
+impl GridTrack {
+    pub fn minmax(min: GridTrack, max: GridTrack) -> Self {
+        GridTrack::MinMax(Box::new(min), Box::new(max))
+    }
+
+    fn min_sizing(&self) -> MinTrackSizingFunction {
+        match self {
+            GridTrack::Length(value) => MinTrackSizingFunction::Fixed(LengthPercentage::Length(*value)),
+            GridTrack::Percent(value) => MinTrackSizingFunction::Fixed(LengthPercentage::Percent(*value)),
+            GridTrack::MinContent => MinTrackSizingFunction::MinContent,
+            GridTrack::MaxContent => MinTrackSizingFunction::MaxContent,
+            GridTrack::MinMax(min, _) => min.min_sizing(),
+            GridTrack::Fr(_) | GridTrack::Auto => MinTrackSizingFunction::Auto,
+        }
+    }
+
+    fn max_sizing(&self) -> MaxTrackSizingFunction {
+        match self {
+            GridTrack::Length(value) => MaxTrackSizingFunction::Fixed(LengthPercentage::Length(*value)),
+            GridTrack::Percent(value) => MaxTrackSizingFunction::Fixed(LengthPercentage::Percent(*value)),
+            GridTrack::Fr(value) => MaxTrackSizingFunction::Fraction(*value),
+            GridTrack::Auto => MaxTrackSizingFunction::Auto,
+            GridTrack::MinContent => MaxTrackSizingFunction::MinContent,
+            GridTrack::MaxContent => MaxTrackSizingFunction::MaxContent,
+            GridTrack::MinMax(_, max) => max.max_sizing(),
+        }
+    }
+
+    pub(crate) fn to_taffy(&self) -> TrackSizingFunction {
+        TrackSizingFunction::Single(MinMax {
+            min: self.min_sizing(),
+            max: self.max_sizing(),
+        })
+    }
+}
+
+/// Placement of a grid item, lines start at 1 and line 0 lets the grid place the item.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridPlacement {
+    pub line: i16,
+    pub span: u16,
+}
+
+impl Default for GridPlacement {
+    fn default() -> Self {
+        Self { line: 0, span: 1 }
+    }
+}
+
+impl GridPlacement {
+    pub(crate) fn to_taffy(&self) -> Line<taffy::GridPlacement> {
+        Line {
+            start: match self.line {
+                0 => taffy::GridPlacement::Auto,
+                line => taffy::GridPlacement::Line(line.into()),
+            },
+            end: match self.span {
+                0 | 1 => taffy::GridPlacement::Auto,
+                span => taffy::GridPlacement::Span(span),
+            },
+        }
+    }
+}
+
 pub(crate) type Drawfunc = Arc<dyn Fn(&mut Element) + Send + Sync + 'static>;
 pub(crate) type Callback = Arc<dyn Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static>;
+pub(crate) type KeyCallback = Arc<dyn Fn(&mut Element, &KeyboardInput, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static>;
+pub(crate) type TextCallback = Arc<dyn Fn(&mut Element, &str, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static>;
 pub(crate) type Renderback = Arc<dyn Fn(&mut ShapePainter) + Send + Sync + 'static>;
 pub(crate) type Shape = Arc<RwLock<dyn ShapeTrait>>;
+pub(crate) type RowBuilder = Arc<dyn Fn(usize) -> Box<dyn UIElement> + Send + Sync + 'static>;
+pub(crate) type DropCallback = Arc<dyn Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static>;
+pub(crate) type DropFilter = Arc<dyn Fn(&DragPayload) -> bool + Send + Sync + 'static>;
+pub(crate) type GestureCallback = Arc<dyn Fn(&mut Element, Gesture, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static>;
+pub(crate) type MenuBuilder = Arc<dyn Fn() -> Box<dyn UIElement> + Send + Sync + 'static>;
+
+/// Rows of a virtual list, only the visible ones are built with `row`.
+#[derive(Clone)]
+pub(crate) struct VirtualRows {
+    pub(crate) count: usize,
+    pub(crate) row_height: f32,
+    pub(crate) row: RowBuilder,
+}
 
 #[derive(Clone)]
 pub struct RenderAction {
@@ -82,6 +362,25 @@ impl RenderAction {
 pub struct IunputAction {
     pub(crate) hover: Option<Callback>,
     pub(crate) click: Option<Callback>,
+    pub(crate) double_click: Option<Callback>,
+    pub(crate) click_capture: Option<Callback>,
+    pub(crate) drag_start: Option<Callback>,
+    pub(crate) drag: Option<Callback>,
+    pub(crate) drag_end: Option<Callback>,
+    pub(crate) right_click: Option<Callback>,
+    pub(crate) context_menu: Option<MenuBuilder>,
+    pub(crate) middle_click: Option<Callback>,
+    pub(crate) middle_drag: Option<Callback>,
+    pub(crate) selection_change: Option<Callback>,
+    pub(crate) gesture: Option<GestureCallback>,
+    pub(crate) drop_filter: Option<DropFilter>,
+    pub(crate) drag_enter: Option<DropCallback>,
+    pub(crate) drag_over: Option<DropCallback>,
+    pub(crate) drag_leave: Option<DropCallback>,
+    pub(crate) drop: Option<DropCallback>,
+    pub(crate) key_down: Option<KeyCallback>,
+    pub(crate) key_up: Option<KeyCallback>,
+    pub(crate) text_input: Option<TextCallback>,
 }
 
 impl Default for IunputAction {
@@ -89,6 +388,25 @@ impl Default for IunputAction {
         Self {
             hover: None,
             click: None,
+            double_click: None,
+            click_capture: None,
+            drag_start: None,
+            drag: None,
+            drag_end: None,
+            right_click: None,
+            context_menu: None,
+            middle_click: None,
+            middle_drag: None,
+            selection_change: None,
+            gesture: None,
+            drop_filter: None,
+            drag_enter: None,
+            drag_over: None,
+            drag_leave: None,
+            drop: None,
+            key_down: None,
+            key_up: None,
+            text_input: None,
         }
     }
 }
@@ -98,14 +416,39 @@ pub struct Element {
     id: i64,
     zorder: i32,
     pub(crate) tile: String,
+    key: Option<String>,
     color: Srgba,
     background_color: Srgba,
     round: Vec4,
+    width: Length,
+    height: Length,
+    min_size: Size<Length>,
+    max_size: Size<Length>,
+    aspect_ratio: Option<f32>,
+    position: Position,
+    inset: Rect<Length>,
+    anchor: Option<(Anchor, Vec2)>,
+    scroll_axes: Option<ScrollAxes>,
+    virtual_rows: Option<VirtualRows>,
+    scroll_offset: Vec2,
+    scrollbar_size: Vec2,
+    /// Visible area left by the scroll views above the element, in painter space.
+    pub(crate) clip: Option<Clip>,
+    overflow: Overflow,
+    focusable: bool,
+    tab_index: i32,
+    dragging: bool,
+    drag_payload: Option<DragPayload>,
+    /// Whether the payload dragged over the element is accepted, for the drop feedback.
+    pub(crate) drop_state: Option<bool>,
+    selectable: bool,
+    pub(crate) selected: bool,
+    pub(crate) focused: bool,
     layout_size: Vec2,
     pub(crate) layout_anchor: Vec3,
     pub(crate) content_size: Vec2,
-    action_state: UIMouseState,
-    render_state: UIMouseState,
+    pub(crate) action_state: UIMouseState,
+    pub(crate) render_state: UIMouseState,
     render_block: UIRenderMode,
     pub anchor_offset: Vec3,
     pub rubber_offset: Vec3,
@@ -121,8 +464,25 @@ pub struct Element {
     pub(crate) cors_axis_alignment: AlignItems,
     pub(crate) self_main_axis_alignment: AlignItems,
     pub(crate) self_cors_axis_alignment: AlignItems,
+    flex_wrap: FlexWrap,
+    flex_grow: f32,
+    flex_shrink: f32,
+    flex_basis: Length,
+    display: Display,
+    grid_template_rows: Vec<GridTrack>,
+    grid_template_columns: Vec<GridTrack>,
+    gap: Vec2,
+    grid_row: GridPlacement,
+    grid_column: GridPlacement,
     element_type: ElementType,
     drag_enable: bool,
+    cursor: Option<CursorIcon>,
+    /// Moved by the group drag of the selection instead of its own drag.
+    pub(crate) group_drag: bool,
+    /// Children removed together with the element, pushed back under it with their ids.
+    pub(crate) detached: Vec<Element>,
+    /// Depth right above the last drawn descendant, where the scrollbars go.
+    pub(crate) scrollbar_depth: f32,
     isready: bool,
 }
 
@@ -131,9 +491,32 @@ impl Element {
         Self {
             id: IdInstance::next_id(),
             tile: "element".to_string(),
+            key: None,
             color: Srgba::ZERO,
             background_color: Srgba::ZERO,
             round: Vec4::ZERO,
+            width: Length::Auto,
+            height: Length::Auto,
+            min_size: Size { width: Length::Auto, height: Length::Auto },
+            max_size: Size { width: Length::Auto, height: Length::Auto },
+            aspect_ratio: None,
+            position: Position::Relative,
+            inset: Rect { left: Length::Auto, right: Length::Auto, top: Length::Auto, bottom: Length::Auto },
+            anchor: None,
+            scroll_axes: None,
+            virtual_rows: None,
+            scroll_offset: Vec2::ZERO,
+            scrollbar_size: Vec2::ZERO,
+            clip: None,
+            overflow: Overflow::Visible,
+            focusable: false,
+            tab_index: 0,
+            dragging: false,
+            drag_payload: None,
+            drop_state: None,
+            selectable: false,
+            selected: false,
+            focused: false,
             layout_size: Vec2::ZERO,
             layout_anchor: Vec3::ZERO,
             content_size: Vec2::ZERO,
@@ -156,12 +539,32 @@ impl Element {
             cors_axis_alignment: AlignItems::Start,
             self_main_axis_alignment: AlignItems::NotSet,
             self_cors_axis_alignment: AlignItems::NotSet,
+            flex_wrap: FlexWrap::NoWrap,
+            flex_grow: 0.,
+            flex_shrink: 1.,
+            flex_basis: Length::Auto,
+            display: Display::Flex,
+            grid_template_rows: Vec::new(),
+            grid_template_columns: Vec::new(),
+            gap: Vec2::ZERO,
+            grid_row: GridPlacement::default(),
+            grid_column: GridPlacement::default(),
             element_type: ElementType::Content,
             drag_enable: false,
+            cursor: None,
+            group_drag: false,
+            detached: Vec::new(),
+            scrollbar_depth: 0.,
         }
     }
 
     pub fn insection(&self, point: Vec2) -> bool {
+        if let Some(clip) = self.clip {
+            if !clip.contains(Vec2::new(point.x, -point.y)) {
+                return false;
+            }
+        }
+
         let left = self.layout_anchor.x - self.layout_size.x / 2.;
         let right = self.layout_anchor.x + self.layout_size.x / 2.;
         let top = -self.layout_anchor.y + self.layout_size.y / 2.;
@@ -199,7 +602,23 @@ impl Element {
         self
     }
 
+    /// User supplied key to find the element again with `find_by_key`. When two elements are
+    /// pushed with the same key, a warning is logged and the last one pushed takes the key.
+    pub fn key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    pub fn get_key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Fixed size in pixels, `Vec2::ZERO` means auto.
     pub fn size(mut self, size: Vec2) -> Self {
+        (self.width, self.height) = match size == Vec2::ZERO {
+            true => (Length::Auto, Length::Auto),
+            false => (Length::Px(size.x), Length::Px(size.y)),
+        };
         self.layout_size = size;
         if let Some(shape) = self.shape.as_ref() {
             shape.write().unwrap().set_size(size);
@@ -207,6 +626,325 @@ impl Element {
         self
     }
 
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn min_size(mut self, width: Length, height: Length) -> Self {
+        self.min_size = Size { width, height };
+        self
+    }
+
+    pub fn max_size(mut self, width: Length, height: Length) -> Self {
+        self.max_size = Size { width, height };
+        self
+    }
+
+    /// Width divided by height, used when only one of them is known.
+    pub fn aspect_ratio(mut self, ratio: f32) -> Self {
+        self.aspect_ratio = Some(ratio);
+        self
+    }
+
+    /// Absolute elements are taken out of the flex/grid flow of their parent. Drops the anchor.
+    pub fn position(mut self, position: Position) -> Self {
+        self.position = position;
+        self.anchor = None;
+        self
+    }
+
+    /// Left, top, right and bottom insets, relative to the parent for absolute elements
+    /// and to the element's own place in the flow for relative ones.
+    pub fn inset(mut self, left: Length, top: Length, right: Length, bottom: Length) -> Self {
+        self.inset = Rect { left, right, top, bottom };
+        self.anchor = None;
+        self
+    }
+
+    /// Position the element absolutely, pinning its `anchor` point to the same point of the parent,
+    /// moved inward by `offset`.
+    pub fn anchor(mut self, anchor: Anchor, offset: Vec2) -> Self {
+        let fraction = anchor.fraction();
+        let inset = |fraction: f32, offset: f32| match fraction {
+            f if f <= 0. => (Length::Px(offset), Length::Auto),
+            f if f >= 1. => (Length::Auto, Length::Px(offset)),
+            f => (Length::Percent(f), Length::Auto),
+        };
+        let (left, right) = inset(fraction.x, offset.x);
+        let (top, bottom) = inset(fraction.y, offset.y);
+        self.position = Position::Absolute;
+        self.inset = Rect { left, right, top, bottom };
+        self.anchor = Some((anchor, offset));
+        self
+    }
+
+    pub fn get_position(&self) -> Position {
+        self.position
+    }
+
+    /// Margin moving a center anchored axis back by a part of the measured `size`, taffy
+    /// places the left/top edge on the anchor point. None without a center anchored axis.
+    pub(crate) fn anchor_margin(&self, size: Size<f32>) -> Option<Rect<LengthPercentageAuto>> {
+        let (anchor, offset) = self.anchor?;
+        let fraction = anchor.fraction();
+        let centered = |fraction: f32| fraction > 0. && fraction < 1.;
+        if !centered(fraction.x) && !centered(fraction.y) {
+            return None;
+        }
+        let margin = |own: f32, fraction: f32, size: f32, offset: f32| match centered(fraction) {
+            true => length(own + offset - size * fraction),
+            false => length(own),
+        };
+        Some(Rect {
+            left: margin(self.margin.x, fraction.x, size.width, offset.x),
+            right: length(self.margin.z),
+            top: margin(self.margin.y, fraction.y, size.height, offset.y),
+            bottom: length(self.margin.w),
+        })
+    }
+
+    /// Clip the children to the element and let them scroll along `axes`.
+    pub fn scroll(mut self, axes: ScrollAxes) -> Self {
+        self.scroll_axes = Some(axes);
+        self
+    }
+
+    /// Turn the element into a vertical scroll view of `count` rows of `row_height`,
+    /// `row` is asked for the element at an index when it scrolls into view.
+    pub fn virtual_rows<E: UIElement>(
+        mut self,
+        count: usize,
+        row_height: f32,
+        row: impl Fn(usize) -> E + Send + Sync + 'static,
+    ) -> Self {
+        self.scroll_axes = Some(ScrollAxes::Vertical);
+        self.flex_direction = FlexDirection::Column;
+        self.virtual_rows = Some(VirtualRows {
+            count,
+            row_height,
+            row: Arc::new(move |index| Box::new(row(index)) as Box<dyn UIElement>),
+        });
+        self
+    }
+
+    pub(crate) fn get_virtual_rows(&self) -> Option<&VirtualRows> {
+        self.virtual_rows.as_ref()
+    }
+
+    pub(crate) fn set_row_count(&mut self, count: usize) {
+        if let Some(rows) = self.virtual_rows.as_mut() {
+            rows.count = count;
+        }
+    }
+
+    pub fn is_scroll_view(&self) -> bool {
+        self.scroll_axes.is_some()
+    }
+
+    pub fn get_scroll_offset(&self) -> Vec2 {
+        self.scroll_offset
+    }
+
+    /// Largest offset the content can scroll to, zero on the axes that don't scroll.
+    pub fn max_scroll(&self) -> Vec2 {
+        let Some(axes) = self.scroll_axes else {
+            return Vec2::ZERO;
+        };
+        let overflow = (self.content_size - self.viewport().size()).max(Vec2::ZERO);
+        Vec2::new(
+            if axes.horizontal() { overflow.x } else { 0. },
+            if axes.vertical() { overflow.y } else { 0. },
+        )
+    }
+
+    pub fn scroll_to(&mut self, offset: Vec2) {
+        self.scroll_offset = offset.clamp(Vec2::ZERO, self.max_scroll());
+    }
+
+    /// Scroll by `delta` and tell whether the offset changed.
+    pub fn scroll_by(&mut self, delta: Vec2) -> bool {
+        let old = self.scroll_offset;
+        self.scroll_to(old + delta);
+        old != self.scroll_offset
+    }
+
+    /// Part of the element left for the content once the scrollbars are reserved, in painter space.
+    pub(crate) fn viewport(&self) -> bevy::math::Rect {
+        let rect = self.get_rect();
+        bevy::math::Rect {
+            min: Vec2::new(rect.min.x, rect.min.y + self.scrollbar_size.y),
+            max: Vec2::new(rect.max.x - self.scrollbar_size.x, rect.max.y),
+        }
+    }
+
+    /// Track and thumb of the vertical or horizontal scrollbar, if that axis overflows.
+    pub(crate) fn scrollbar(&self, vertical: bool) -> Option<(bevy::math::Rect, bevy::math::Rect)> {
+        let max_scroll = self.max_scroll();
+        let viewport = self.viewport();
+        let rect = self.get_rect();
+        let (overflow, visible, offset) = match vertical {
+            true => (max_scroll.y, viewport.height(), self.scroll_offset.y),
+            false => (max_scroll.x, viewport.width(), self.scroll_offset.x),
+        };
+        if overflow <= 0. {
+            return None;
+        }
+        let thumb_length = (visible * visible / (visible + overflow)).max(SCROLLBAR_MIN_THUMB).min(visible);
+        let start = (visible - thumb_length) * offset / overflow;
+        Some(match vertical {
+            true => {
+                let track = bevy::math::Rect::new(viewport.max.x, viewport.min.y, rect.max.x, viewport.max.y);
+                let top = viewport.max.y - start;
+                (track, bevy::math::Rect::new(track.min.x, top - thumb_length, track.max.x, top))
+            }
+            false => {
+                let track = bevy::math::Rect::new(viewport.min.x, rect.min.y, viewport.max.x, viewport.min.y);
+                let left = viewport.min.x + start;
+                (track, bevy::math::Rect::new(left, track.min.y, left + thumb_length, track.max.y))
+            }
+        })
+    }
+
+    /// Scroll offset change for a thumb dragged by `distance` pixels along its track.
+    pub(crate) fn thumb_to_scroll(&self, vertical: bool, distance: f32) -> f32 {
+        let Some((track, thumb)) = self.scrollbar(vertical) else {
+            return 0.;
+        };
+        let (track, thumb, overflow) = match vertical {
+            true => (track.height(), thumb.height(), self.max_scroll().y),
+            false => (track.width(), thumb.width(), self.max_scroll().x),
+        };
+        match track - thumb > 0. {
+            true => distance * overflow / (track - thumb),
+            false => 0.,
+        }
+    }
+
+    /// Drawn after the children so the bars stay on top of the content.
+    pub(crate) fn draw_scrollbars(&self, painter: &mut ShapePainter) {
+        for vertical in [true, false] {
+            if let Some((track, thumb)) = self.scrollbar(vertical) {
+                painter.corner_radii = Vec4::splat(SCROLLBAR_WIDTH / 2.);
+                painter.set_color(GRAY.with_alpha(0.2));
+                painter.set_translation(track.center().extend(self.scrollbar_depth));
+                painter.rect(track.size());
+                painter.set_color(GRAY.with_alpha(0.8));
+                painter.set_translation(thumb.center().extend(self.scrollbar_depth));
+                painter.rect(thumb.size());
+            }
+        }
+        painter.corner_radii = Vec4::ZERO;
+    }
+
+    /// Fill a rect of `size` centered on the element, cut by the clip and rounded
+    /// where it reaches into the rounded corners of the clip.
+    fn fill_rect(&self, painter: &mut ShapePainter, size: Vec2) {
+        let rect = bevy::math::Rect::from_center_size(self.layout_anchor.truncate(), size);
+        let round = painter.corner_radii;
+        let rect = match self.clip {
+            Some(clip) => {
+                let rect = clip.rect.intersect(rect);
+                painter.corner_radii = round.max(clip.fit(rect));
+                rect
+            }
+            None => rect,
+        };
+        if !rect.is_empty() {
+            painter.set_translation(rect.center().extend(self.layout_anchor.z));
+            painter.rect(rect.size());
+            painter.set_translation(self.layout_anchor);
+        }
+        painter.corner_radii = round;
+    }
+
+    /// Let the element take the keyboard focus by click or Tab.
+    pub fn focusable(mut self, focusable: bool) -> Self {
+        self.focusable = focusable;
+        self
+    }
+
+    /// Position in the Tab order, positive indices come first, 0 follows the tree order
+    /// and negative ones are skipped by Tab but can still be focused by click.
+    pub fn tab_index(mut self, tab_index: i32) -> Self {
+        self.focusable = true;
+        self.tab_index = tab_index;
+        self
+    }
+
+    pub fn is_focusable(&self) -> bool {
+        self.focusable
+    }
+
+    pub fn get_tab_index(&self) -> i32 {
+        self.tab_index
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Outline around the element, for the focus and the drop feedback.
+    fn draw_ring(&self, painter: &mut ShapePainter, color: Srgba) {
+        let rect = self.get_rect().inflate(FOCUS_RING_GAP);
+        painter.hollow = true;
+        painter.thickness = 2.;
+        painter.corner_radii = self.round + Vec4::splat(FOCUS_RING_GAP);
+        painter.set_color(color);
+        painter.set_translation(rect.center().extend(self.layout_anchor.z));
+        painter.rect(rect.size());
+        painter.hollow = false;
+    }
+
+    /// Cut drawing and hit-testing of the descendants to the element.
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn get_overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Clip applied to the children, the viewport for scroll views.
+    pub(crate) fn child_clip(&self) -> Option<Clip> {
+        let radii = match self.overflow {
+            Overflow::Visible if !self.is_scroll_view() => return None,
+            Overflow::ClipRounded => self.round,
+            _ => Vec4::ZERO,
+        };
+        let rect = match self.is_scroll_view() {
+            true => self.viewport(),
+            false => self.get_rect(),
+        };
+        Some(Clip { rect, radii })
+    }
+
+    pub fn wrap(mut self, wrap: FlexWrap) -> Self {
+        self.flex_wrap = wrap;
+        self
+    }
+
+    pub fn grow(mut self, grow: f32) -> Self {
+        self.flex_grow = grow;
+        self
+    }
+
+    pub fn shrink(mut self, shrink: f32) -> Self {
+        self.flex_shrink = shrink;
+        self
+    }
+
+    pub fn basis(mut self, basis: Length) -> Self {
+        self.flex_basis = basis;
+        self
+    }
+
     pub fn click(
         mut self,
         action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
@@ -223,6 +961,369 @@ impl Element {
         self
     }
 
+    /// Second click within `MemState::double_click_time`, the click callback runs instead when unset.
+    pub fn double_click(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.double_click = Some(Arc::new(action));
+        self
+    }
+
+    /// Called for left clicks and double clicks on the element or its descendants before their
+    /// own callbacks, from the root down to the clicked element. Click callbacks then bubble
+    /// back up. Hover, drag and right or middle button callbacks only run on their element.
+    pub fn on_click_capture(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.click_capture = Some(Arc::new(action));
+        self
+    }
+
+    /// Whether a press can target the element, presses on the others go to the element below.
+    /// Any element taking pointer, key or drop input stops presses from falling through.
+    pub(crate) fn is_interactive(&self) -> bool {
+        let action = &self.action;
+        self.drag_enable
+            || self.focusable
+            || self.selectable
+            || action.click.is_some()
+            || action.double_click.is_some()
+            || action.click_capture.is_some()
+            || action.drag_start.is_some()
+            || action.drag.is_some()
+            || action.drag_end.is_some()
+            || self.drag_payload.is_some()
+            || self.handles_button(MouseButton::Right)
+            || self.handles_button(MouseButton::Middle)
+            || self.handles_gesture()
+            || action.key_down.is_some()
+            || action.key_up.is_some()
+            || action.text_input.is_some()
+            || self.is_drop_target()
+    }
+
+    /// Run the callback of a `Click` or `DoubleClick` in the capture or bubble phase.
+    pub(crate) fn pointer_event(&mut self, event: UIMouseState, capture: bool, context: &mut RwLockWriteGuard<MemState>) {
+        let action = match (capture, event) {
+            (true, _) => self.action.click_capture.clone(),
+            (false, UIMouseState::DoubleClick) => self.action.double_click.clone().or(self.action.click.clone()),
+            (false, _) => self.action.click.clone(),
+        };
+        if let Some(action) = action {
+            action(self, context);
+        }
+    }
+
+    /// Called once the pressed cursor moved past `MemState::drag_threshold`,
+    /// the drag runs from `drag_delta.0` to `drag_delta.1`.
+    pub fn on_drag_start(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.drag_start = Some(Arc::new(action));
+        self
+    }
+
+    /// Called every frame of a drag after `on_drag_start`.
+    pub fn on_drag(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.drag = Some(Arc::new(action));
+        self
+    }
+
+    /// Called when the button is released, the whole drag is in `drag_delta.2`.
+    pub fn on_drag_end(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.drag_end = Some(Arc::new(action));
+        self
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Let clicks and rubber bands select the element.
+    pub fn selectable(mut self, selectable: bool) -> Self {
+        self.selectable = selectable;
+        self
+    }
+
+    pub fn is_selectable(&self) -> bool {
+        self.selectable
+    }
+
+    pub fn is_selected(&self) -> bool {
+        self.selected
+    }
+
+    /// Called when the element is selected or deselected, `is_selected` tells which.
+    pub fn on_selection_change(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.selection_change = Some(Arc::new(action));
+        self
+    }
+
+    pub(crate) fn set_selected(&mut self, selected: bool, context: &mut RwLockWriteGuard<MemState>) {
+        if self.selected == selected {
+            return;
+        }
+        self.selected = selected;
+        if self.render_state != UIMouseState::Hover {
+            self.render_state = self.idle_state();
+        }
+        if let Some(action) = self.action.selection_change.clone() {
+            action(self, context);
+        }
+    }
+
+    /// Render state of the element when the cursor is not over it.
+    pub(crate) fn idle_state(&self) -> UIMouseState {
+        match self.selected {
+            true => UIMouseState::Selected,
+            false => UIMouseState::Release,
+        }
+    }
+
+    /// Called with the pinch, rotation, double tap and pan gestures over the element,
+    /// also synthesized from Ctrl + wheel and mouse drags.
+    pub fn on_gesture(
+        mut self,
+        action: impl Fn(&mut Element, Gesture, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.gesture = Some(Arc::new(action));
+        self
+    }
+
+    pub(crate) fn handles_gesture(&self) -> bool {
+        self.action.gesture.is_some()
+    }
+
+    pub(crate) fn gesture(&mut self, gesture: Gesture, context: &mut RwLockWriteGuard<MemState>) {
+        if let Some(action) = self.action.gesture.clone() {
+            action(self, gesture, context);
+        }
+    }
+
+    /// Make the element a drag source, dragging it carries `payload` to the drop targets
+    /// while a ghost of the element follows the cursor.
+    pub fn drag_source<T: std::any::Any + Send + Sync>(mut self, payload: T) -> Self {
+        self.drag_payload = Some(DragPayload::new(payload));
+        self
+    }
+
+    pub fn get_drag_payload(&self) -> Option<&DragPayload> {
+        self.drag_payload.as_ref()
+    }
+
+    /// Accept only the payloads for which `filter` is true, the drop callbacks
+    /// are not called for the others. Drop targets accept everything by default.
+    pub fn drop_filter(mut self, filter: impl Fn(&DragPayload) -> bool + Send + Sync + 'static) -> Self {
+        self.action.drop_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Accept only payloads of type `T`.
+    pub fn accepts<T: std::any::Any>(self) -> Self {
+        self.drop_filter(|payload| payload.is::<T>())
+    }
+
+    pub fn on_drag_enter(
+        mut self,
+        action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.drag_enter = Some(Arc::new(action));
+        self
+    }
+
+    /// Called every frame an accepted payload stays over the element.
+    pub fn on_drag_over(
+        mut self,
+        action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.drag_over = Some(Arc::new(action));
+        self
+    }
+
+    pub fn on_drag_leave(
+        mut self,
+        action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.drag_leave = Some(Arc::new(action));
+        self
+    }
+
+    pub fn on_drop(
+        mut self,
+        action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.drop = Some(Arc::new(action));
+        self
+    }
+
+    pub(crate) fn is_drop_target(&self) -> bool {
+        let action = &self.action;
+        action.drop_filter.is_some()
+            || action.drag_enter.is_some()
+            || action.drag_over.is_some()
+            || action.drag_leave.is_some()
+            || action.drop.is_some()
+    }
+
+    pub(crate) fn accepts_payload(&self, payload: &DragPayload) -> bool {
+        self.action.drop_filter.as_ref().map_or(true, |filter| filter(payload))
+    }
+
+    pub(crate) fn drop_event(&mut self, event: DropEvent, payload: &DragPayload, context: &mut RwLockWriteGuard<MemState>) {
+        let action = match event {
+            DropEvent::Enter => self.action.drag_enter.clone(),
+            DropEvent::Over => self.action.drag_over.clone(),
+            DropEvent::Leave => self.action.drag_leave.clone(),
+            DropEvent::Drop => self.action.drop.clone(),
+        };
+        if let Some(action) = action {
+            action(self, payload, context);
+        }
+    }
+
+    /// Copy of the element centered on `center` at depth `z`, its fill and shape faded by `alpha`,
+    /// drawn under the cursor while it is dragged.
+    pub(crate) fn draw_ghost(&self, painter: &mut ShapePainter, center: Vec2, z: f32, alpha: f32) {
+        let fade = |color: Srgba| color.with_alpha(color.alpha * alpha);
+        let color = match self.background_color != Srgba::ZERO {
+            true => self.background_color,
+            false => self.color,
+        };
+        painter.corner_radii = self.round;
+        painter.set_color(fade(color));
+        painter.set_translation(center.extend(z));
+        painter.rect(self.layout_size);
+        if let Some(shape) = self.shape.as_ref() {
+            painter.set_color(fade(self.color));
+            shape.read().unwrap().draw(painter);
+        }
+        painter.corner_radii = Vec4::ZERO;
+    }
+
+    pub fn on_right_click(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.right_click = Some(Arc::new(action));
+        self
+    }
+
+    /// Menu opened at the cursor on right click, it closes on a click inside it or a press outside.
+    pub fn on_context_menu<E: UIElement>(mut self, menu: impl Fn() -> E + Send + Sync + 'static) -> Self {
+        self.action.context_menu = Some(Arc::new(move || Box::new(menu()) as Box<dyn UIElement>));
+        self
+    }
+
+    pub fn on_middle_click(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.middle_click = Some(Arc::new(action));
+        self
+    }
+
+    /// Called while the middle button pressed on the element moves, with the movement in
+    /// `MemState::pan_delta`. Panning a canvas is `element.scroll_by(-cxt.pan_delta)`.
+    pub fn on_middle_drag(
+        mut self,
+        action: impl Fn(&mut Element, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.middle_drag = Some(Arc::new(action));
+        self
+    }
+
+    /// Whether the element reacts to presses of the right or middle `button`.
+    pub(crate) fn handles_button(&self, button: MouseButton) -> bool {
+        match button {
+            MouseButton::Right => self.action.right_click.is_some() || self.action.context_menu.is_some(),
+            MouseButton::Middle => self.action.middle_click.is_some() || self.action.middle_drag.is_some(),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn button_click(&mut self, button: MouseButton, context: &mut RwLockWriteGuard<MemState>) {
+        let action = match button {
+            MouseButton::Right => self.action.right_click.clone(),
+            MouseButton::Middle => self.action.middle_click.clone(),
+            _ => None,
+        };
+        if let Some(action) = action {
+            action(self, context);
+        }
+    }
+
+    pub(crate) fn middle_drag(&mut self, context: &mut RwLockWriteGuard<MemState>) {
+        if let Some(action) = self.action.middle_drag.clone() {
+            action(self, context);
+        }
+    }
+
+    pub(crate) fn get_context_menu(&self) -> Option<MenuBuilder> {
+        self.action.context_menu.clone()
+    }
+
+    /// Called with the key presses received while the element has the focus, before the
+    /// shortcuts. `MemState::prevent_default` keeps the key from triggering them, so Enter
+    /// and Space don't activate the element.
+    pub fn on_key_down(
+        mut self,
+        action: impl Fn(&mut Element, &KeyboardInput, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.key_down = Some(Arc::new(action));
+        self
+    }
+
+    pub fn on_key_up(
+        mut self,
+        action: impl Fn(&mut Element, &KeyboardInput, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.key_up = Some(Arc::new(action));
+        self
+    }
+
+    /// Called with the text typed while the element has the focus, plain keys then stop
+    /// triggering the shortcuts without ctrl or alt.
+    pub fn on_text_input(
+        mut self,
+        action: impl Fn(&mut Element, &str, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> Self {
+        self.action.text_input = Some(Arc::new(action));
+        self
+    }
+
+    pub fn accepts_text(&self) -> bool {
+        self.action.text_input.is_some()
+    }
+
+    /// Run the key callbacks of the element for `event`.
+    pub(crate) fn key_input(&mut self, event: &KeyboardInput, context: &mut RwLockWriteGuard<MemState>) {
+        let action = match event.state {
+            ButtonState::Pressed => self.action.key_down.clone(),
+            ButtonState::Released => self.action.key_up.clone(),
+        };
+        if let Some(action) = action {
+            action(self, event, context);
+        }
+    }
+
+    pub(crate) fn text_input(&mut self, text: &str, context: &mut RwLockWriteGuard<MemState>) {
+        if let Some(action) = self.action.text_input.clone() {
+            action(self, text, context);
+        }
+    }
+
     pub fn primatives(mut self, draw: Option<Callback>) -> Self {
         self.draw = draw;
         self
@@ -245,11 +1346,114 @@ impl Element {
         self.layout_size
     }
 
+    pub fn get_title(&self) -> &str {
+        self.tile.as_str()
+    }
+
+    pub fn get_width(&self) -> Length {
+        self.width
+    }
+
+    pub fn get_height(&self) -> Length {
+        self.height
+    }
+
+    pub fn get_color(&self) -> Srgba {
+        self.color
+    }
+
+    pub fn get_background_color(&self) -> Srgba {
+        self.background_color
+    }
+
+    pub fn get_margin(&self) -> Vec4 {
+        self.margin
+    }
+
+    pub fn get_padding(&self) -> Vec4 {
+        self.padding
+    }
+
+    pub fn get_round(&self) -> Vec4 {
+        self.round
+    }
+
+    /// Change a property from the inspector, `Element::style()` picks it up on the next restyle.
+    pub(crate) fn apply_edit(&mut self, field: InspectorField, delta: f32) {
+        match field {
+            InspectorField::Width | InspectorField::Height => {
+                let (length, current) = match field {
+                    InspectorField::Width => (&mut self.width, self.layout_size.x),
+                    _ => (&mut self.height, self.layout_size.y),
+                };
+                let current = match *length {
+                    Length::Px(value) => value,
+                    _ => current,
+                };
+                *length = Length::Px((current + delta).max(0.));
+                let size = Vec2::new(
+                    match self.width { Length::Px(value) => value, _ => self.layout_size.x },
+                    match self.height { Length::Px(value) => value, _ => self.layout_size.y },
+                );
+                if let Some(shape) = self.shape.as_ref() {
+                    shape.write().unwrap().set_size(size);
+                }
+            }
+            InspectorField::Margin => {
+                self.margin += Vec4::splat(delta);
+            }
+            InspectorField::Padding => {
+                self.padding = (self.padding + Vec4::splat(delta)).max(Vec4::ZERO);
+            }
+            InspectorField::Round => {
+                self.round = (self.round + Vec4::splat(delta)).max(Vec4::ZERO);
+                if let Some(shape) = self.shape.as_ref() {
+                    shape.write().unwrap().set_round(self.round);
+                }
+            }
+            InspectorField::ZOrder => {
+                self.zorder += delta as i32;
+            }
+            InspectorField::Color(channel) => {
+                self.color = channel.apply(self.color, delta);
+            }
+            InspectorField::BackgroundColor(channel) => {
+                self.background_color = channel.apply(self.background_color, delta);
+            }
+        }
+    }
+
+    /// Center of the shape and the text of the element, just above its own fills.
+    pub(crate) fn shape_anchor(&self) -> Vec3 {
+        self.layout_anchor + Vec3::Z * SHAPE_LIFT
+    }
+
+    /// Whether the clip leaves some of the shape, sized by the shape when it knows its size
+    /// since text doesn't size its element.
+    pub(crate) fn shape_visible(&self) -> bool {
+        let Some(clip) = self.clip else {
+            return true;
+        };
+        let size = self.shape.as_ref().and_then(|shape| shape.read().unwrap().get_size()).unwrap_or(self.layout_size);
+        clip.overlaps(bevy::math::Rect::from_center_size(self.layout_anchor.truncate(), size))
+    }
+
+    /// Drawn rect of the element, centered on `layout_anchor`.
+    pub fn get_rect(&self) -> bevy::math::Rect {
+        bevy::math::Rect::from_center_size(self.layout_anchor.truncate(), self.layout_size)
+    }
+
+    /// Z-order among the siblings, higher ones are drawn above and hit first.
+    /// Each element is a stacking context, its descendants never go above or below its siblings.
     pub fn order(mut self, zorder: i32) -> Self {
         self.zorder = zorder;
         self
     }
 
+    pub(crate) fn is_group(&self) -> bool {
+        self.render_block == UIRenderMode::Group
+    }
+
     pub fn direction(mut self, direction: FlexDirection) -> Self {
         self.flex_direction = direction;
         self
@@ -260,6 +1464,51 @@ impl Element {
         self
     }
 
+    pub fn display(mut self, display: Display) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Row tracks of a grid container, switches the element to `Display::Grid`.
+    pub fn grid_template_rows(mut self, rows: Vec<GridTrack>) -> Self {
+        self.display = Display::Grid;
+        self.grid_template_rows = rows;
+        self
+    }
+
+    /// Column tracks of a grid container, switches the element to `Display::Grid`.
+    pub fn grid_template_columns(mut self, columns: Vec<GridTrack>) -> Self {
+        self.display = Display::Grid;
+        self.grid_template_columns = columns;
+        self
+    }
+
+    /// Space between rows (`y`) and columns (`x`) of a grid or a wrapping flex container.
+    pub fn gap(mut self, gap: Vec2) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Place the element on grid row `line` spanning `span` rows, `line` 0 keeps auto placement.
+    pub fn grid_row(mut self, line: i16, span: u16) -> Self {
+        self.grid_row = GridPlacement { line, span };
+        self
+    }
+
+    /// Place the element on grid column `line` spanning `span` columns, `line` 0 keeps auto placement.
+    pub fn grid_column(mut self, line: i16, span: u16) -> Self {
+        self.grid_column = GridPlacement { line, span };
+        self
+    }
+
+    pub fn get_display(&self) -> Display {
+        self.display
+    }
+
+    pub fn get_gap(&self) -> Vec2 {
+        self.gap
+    }
+
     pub fn offset(mut self, offset: Vec3) -> Self {
         self.anchor_offset = offset;
         self
@@ -362,13 +1611,18 @@ impl Element {
         self
     }
 
-    pub fn id(&mut self) -> i64 {
+    pub fn id(&self) -> i64 {
         return self.id;
     }
 
+    /// Clones share the id of their source, a clone pushed next to it gets a fresh one.
+    pub(crate) fn renew_id(&mut self) {
+        self.id = IdInstance::next_id();
+    }
+
     fn drag(&mut self, delta:Vec2){
         match self.action_state {
-            UIMouseState::Pressed => {
+            UIMouseState::Pressed | UIMouseState::Drag => {
                 self.rubber_offset.x = delta.x; 
                 self.rubber_offset.y = -delta.y;
             },
@@ -380,10 +1634,49 @@ impl Element {
         self.drag_enable = enable;
         self
     }
+
+    pub fn is_drag_enabled(&self) -> bool {
+        self.drag_enable
+    }
+
+    /// Cursor of the window while the element is hovered or pressed.
+    pub fn cursor(mut self, icon: CursorIcon) -> Self {
+        self.cursor = Some(icon);
+        self
+    }
+
+    /// The cursor set on the element, or the one its input implies: a text beam for
+    /// text fields, move for draggable elements and a hand for clickable ones.
+    pub fn get_cursor(&self) -> Option<CursorIcon> {
+        let action = &self.action;
+        if self.cursor.is_some() {
+            self.cursor
+        } else if self.accepts_text() {
+            Some(CursorIcon::Text)
+        } else if self.drag_enable {
+            Some(CursorIcon::Move)
+        } else if action.click.is_some() || action.double_click.is_some() || action.click_capture.is_some() {
+            Some(CursorIcon::Pointer)
+        } else {
+            None
+        }
+    }
+
+    /// Cursor asked by the element in its current hover state.
+    pub(crate) fn hover_cursor(&self) -> Option<CursorIcon> {
+        let active = self.render_state == UIMouseState::Hover
+            || matches!(self.action_state, UIMouseState::Pressed | UIMouseState::Drag);
+        active.then(|| self.get_cursor()).flatten()
+    }
 }
 
 impl UIElement for Element {
     fn draw(&self, painter: &mut ShapePainter) {
+        if let Some(clip) = self.clip {
+            if clip.rect.intersect(self.get_rect()).is_empty() {
+                return;
+            }
+        }
         painter.set_color(self.color);
         if self.render_block != UIRenderMode::WithoutSelf {
             match self.render_state {
@@ -406,10 +1699,14 @@ impl UIElement for Element {
             }
         }
 
-        painter.set_translation(self.layout_anchor);
+        painter.set_translation(self.shape_anchor());
         if let Some(shape) = self.shape.as_ref() {
-            shape.read().unwrap().draw(painter);
+            match self.clip {
+                Some(clip) => shape.read().unwrap().draw_clipped(painter, self.shape_anchor(), clip),
+                None => shape.read().unwrap().draw(painter),
+            }
         }
+        painter.set_translation(self.layout_anchor);
 
         painter.corner_radii = self.round;
 
@@ -420,14 +1717,26 @@ impl UIElement for Element {
                 // painter.rect(shape.read().unwrap().get_size().unwrap());
             } else {
                 painter.set_color(self.color);
-                painter.rect(self.content_size);
+                self.fill_rect(painter, self.content_size);
             }
         }
 
         // layout rect
         if self.background_color != Srgba::ZERO {
             painter.set_color(self.background_color * 0.5);
-            painter.rect(self.layout_size);
+            self.fill_rect(painter, self.layout_size);
+        }
+
+        if self.selected {
+            self.draw_ring(painter, GOLD);
+        }
+        if self.focused {
+            self.draw_ring(painter, DODGER_BLUE);
+        }
+        match self.drop_state {
+            Some(true) => self.draw_ring(painter, LIMEGREEN),
+            Some(false) => self.draw_ring(painter, ORANGE_RED),
+            None => {}
         }
 
         painter.corner_radii = Vec4::ZERO;
@@ -435,21 +1744,32 @@ impl UIElement for Element {
     }
 
     fn execute(&mut self, context: &mut RwLockWriteGuard<MemState>) {
+        if self.dragging && self.action_state != UIMouseState::Drag {
+            self.dragging = false;
+            if let Some(action) = self.action.drag_end.clone() {
+                action(self, context);
+            }
+        }
         match self.action_state {
             UIMouseState::Hover | UIMouseState::Pressed | UIMouseState::Release => {
                 if let Some(action) = self.action.hover.clone() {
                     action(self, context);
                 }
             }
-            UIMouseState::Click => {
-                if let Some(action) = self.action.click.clone() {
+            // clicks are dispatched along the parent chain by `UILayouts::exc_action`
+            UIMouseState::Click | UIMouseState::DoubleClick => {
+                self.action_state = UIMouseState::Release;
+            }
+            UIMouseState::Drag => {
+                let action = match self.dragging {
+                    false => self.action.drag_start.clone(),
+                    true => self.action.drag.clone(),
+                };
+                self.dragging = true;
+                if let Some(action) = action {
                     action(self, context);
                 }
-                self.action_state = UIMouseState::Release;
             }
-            // UIMouseState::Release => {}
-            UIMouseState::DoubleClick => todo!(),
-            UIMouseState::Drag => todo!(),
             UIMouseState::NoneBlock => {}
             _ => {}
         }
@@ -457,13 +1777,37 @@ impl UIElement for Element {
 
     fn style(&self) -> Style {
         let mut def = Style {
-            size: match (self.layout_size.x, self.layout_size.y) {
-                (0., 0.) => Size::auto(),
-                _ => Size {
-                    width: Dimension::Length(self.layout_size.x),
-                    height: Dimension::Length(self.layout_size.y),
+            size: Size {
+                width: self.width.to_dimension(),
+                height: self.height.to_dimension(),
+            },
+            min_size: self.min_size.map(|length| length.to_dimension()),
+            max_size: self.max_size.map(|length| length.to_dimension()),
+            aspect_ratio: self.aspect_ratio,
+            flex_wrap: match self.flex_wrap {
+                FlexWrap::NoWrap => taffy::FlexWrap::NoWrap,
+                FlexWrap::Wrap => taffy::FlexWrap::Wrap,
+                FlexWrap::WrapReverse => taffy::FlexWrap::WrapReverse,
+            },
+            position: match self.position {
+                Position::Relative => taffy::Position::Relative,
+                Position::Absolute => taffy::Position::Absolute,
+            },
+            inset: self.inset.map(|length| length.to_inset()),
+            overflow: match self.scroll_axes {
+                Some(axes) => taffy::Point {
+                    x: if axes.horizontal() { taffy::Overflow::Scroll } else { taffy::Overflow::Hidden },
+                    y: if axes.vertical() { taffy::Overflow::Scroll } else { taffy::Overflow::Hidden },
+                },
+                None => match self.overflow {
+                    Overflow::Visible => taffy::Point { x: taffy::Overflow::Visible, y: taffy::Overflow::Visible },
+                    Overflow::Hidden | Overflow::ClipRounded => taffy::Point { x: taffy::Overflow::Hidden, y: taffy::Overflow::Hidden },
                 },
             },
+            scrollbar_width: SCROLLBAR_WIDTH,
+            flex_grow: self.flex_grow,
+            flex_shrink: self.flex_shrink,
+            flex_basis: self.flex_basis.to_dimension(),
             margin: Rect {
                 left: length(self.margin.x),
                 right: length(self.margin.z),
@@ -532,12 +1876,24 @@ impl UIElement for Element {
                 AlignItems::Stretch => Some(taffy::AlignSelf::Stretch),
                 AlignItems::NotSet => None,
             },
+            display: match self.display {
+                Display::Flex => taffy::Display::Flex,
+                Display::Grid => taffy::Display::Grid,
+            },
+            gap: Size {
+                width: length(self.gap.x),
+                height: length(self.gap.y),
+            },
+            grid_template_rows: self.grid_template_rows.iter().map(|track| track.to_taffy()).collect(),
+            grid_template_columns: self.grid_template_columns.iter().map(|track| track.to_taffy()).collect(),
+            grid_row: self.grid_row.to_taffy(),
+            grid_column: self.grid_column.to_taffy(),
             ..Default::default()
         };
         match self.element_type {
             ElementType::Debug => {
                 def = Style {
-                    position: Position::Absolute,
+                    position: taffy::Position::Absolute,
                     inset: Rect {
                         left: auto(),
                         right: length(0.0),
@@ -548,6 +1904,18 @@ impl UIElement for Element {
                     ..def
                 }
             }
+            ElementType::Inspector => {
+                def = Style {
+                    position: taffy::Position::Absolute,
+                    inset: Rect {
+                        left: length(10.0),
+                        right: auto(),
+                        top: length(10.0),
+                        bottom: auto(),
+                    },
+                    ..def
+                }
+            }
             ElementType::Content => {}
         }
         def
@@ -566,13 +1934,26 @@ impl UIElement for Element {
     }
 
     fn update_layout(&mut self, layout: &taffy::Layout, origin: Vec3, inherit_origin: Vec3, cxt:&mut RwLockWriteGuard<MemState>) {
-        self.layout_size.x = layout.size.width;
-        self.layout_size.y = layout.size.height;
+        let layout_size = Vec2::new(layout.size.width, layout.size.height);
+        if layout_size != self.layout_size {
+            self.layout_size = layout_size;
+            // percent, grow and min/max sizes are only known here, keep the shape in step with them
+            if let Some(shape) = self.shape.as_ref() {
+                shape.write().unwrap().set_size(layout_size);
+            }
+        }
         self.content_size.x = layout.content_size.width;
         self.content_size.y = layout.content_size.height;
+        self.scrollbar_size.x = layout.scrollbar_size.width;
+        self.scrollbar_size.y = layout.scrollbar_size.height;
+        self.scroll_to(self.scroll_offset);
+
+        if self.action_state == UIMouseState::Pressed && cxt.is_dragging() {
+            self.action_state = UIMouseState::Drag;
+        }
 
-        if self.drag_enable{
-            if self.action_state == UIMouseState::Pressed  {
+        if self.drag_enable && !self.group_drag {
+            if self.action_state == UIMouseState::Pressed || self.action_state == UIMouseState::Drag {
                 self.drag(cxt.drag_delta.1 - cxt.drag_delta.0);
             }
             else if self.action_state == UIMouseState::Release {
@@ -599,7 +1980,7 @@ impl UIElement for Element {
         if self.insection(curo_screen) {
             self.render_state = UIMouseState::Hover;
         } else {
-            self.render_state = UIMouseState::Release;
+            self.render_state = self.idle_state();
         }
     }
 
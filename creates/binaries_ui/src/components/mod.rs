@@ -27,6 +27,7 @@ pub enum UIRenderMode {
 }
 
 use crate::shape::{Circle, Ngon, Rectangle, Text};
+use crate::traits::UIElement;
 
 pub fn element() -> Element
 {
@@ -58,6 +59,12 @@ pub fn ngon(sides:f32) -> Element
     Element::new().color(Srgba::WHITE).shape(Ngon::default().sides(sides)).title("ngon")
 }
 
+/// Scrollable list of `count` rows, `row` builds the element at an index when it becomes visible.
+pub fn virtual_list<E: UIElement>(count: usize, row_height: f32, row: impl Fn(usize) -> E + Send + Sync + 'static) -> Element
+{
+    Element::new().color(Srgba::ZERO).virtual_rows(count, row_height, row).title("virtual list")
+}
+
 #[cfg(test)]
 mod tests {
     use bevy::log::trace;
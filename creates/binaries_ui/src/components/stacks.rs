@@ -1,12 +1,16 @@
 use std::sync::RwLockWriteGuard;
 
 use crate::context::MemState;
+use crate::drag_drop::DragPayload;
+use crate::gesture::Gesture;
 use crate::layout::UILayouts;
 use crate::shape::ShapeTrait;
 use bevy::color::Srgba;
+use bevy::input::keyboard::KeyboardInput;
 use bevy::math::{Vec2, Vec3, Vec4};
+use bevy::window::CursorIcon;
 
-use super::element::{ AlignItems, Element};
+use super::element::{ AlignItems, Anchor, Display, Element, FlexWrap, GridTrack, Length, Overflow, Position, ScrollAxes};
 use super::element_set::ElementSet;
 use super::{UIMouseState, UIRenderMode};
 use crate::traits::UIElement;
@@ -28,6 +32,21 @@ where
     stack(children).direction(FlexDirection::Row).title("hstack")
 }
 
+pub fn grid<K>(children: K) -> Stack<K>
+where
+    K: ElementSet,
+{
+    stack(children).display(Display::Grid).title("grid")
+}
+
+/// Column clipped to its size, children need `shrink(0.)` to keep their size and overflow it.
+pub fn scroll_view<K>(children: K, axes: ScrollAxes) -> Stack<K>
+where
+    K: ElementSet,
+{
+    stack(children).direction(FlexDirection::Column).scroll(axes).title("scroll view")
+}
+
 pub fn stack<K>(children: K) -> Stack<K>
 where
     K: ElementSet,
@@ -63,6 +82,86 @@ where
         self
     }
 
+    pub fn width(mut self, width: Length) -> Self {
+        self.element = self.element.width(width);
+        self
+    }
+
+    pub fn height(mut self, height: Length) -> Self {
+        self.element = self.element.height(height);
+        self
+    }
+
+    pub fn min_size(mut self, width: Length, height: Length) -> Self {
+        self.element = self.element.min_size(width, height);
+        self
+    }
+
+    pub fn max_size(mut self, width: Length, height: Length) -> Self {
+        self.element = self.element.max_size(width, height);
+        self
+    }
+
+    pub fn aspect_ratio(mut self, ratio: f32) -> Self {
+        self.element = self.element.aspect_ratio(ratio);
+        self
+    }
+
+    pub fn position(mut self, position: Position) -> Self {
+        self.element = self.element.position(position);
+        self
+    }
+
+    pub fn inset(mut self, left: Length, top: Length, right: Length, bottom: Length) -> Self {
+        self.element = self.element.inset(left, top, right, bottom);
+        self
+    }
+
+    pub fn anchor(mut self, anchor: Anchor, offset: Vec2) -> Self {
+        self.element = self.element.anchor(anchor, offset);
+        self
+    }
+
+    pub fn focusable(mut self, focusable: bool) -> Self {
+        self.element = self.element.focusable(focusable);
+        self
+    }
+
+    pub fn tab_index(mut self, tab_index: i32) -> Self {
+        self.element = self.element.tab_index(tab_index);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.element = self.element.overflow(overflow);
+        self
+    }
+
+    pub fn scroll(mut self, axes: ScrollAxes) -> Self {
+        self.element = self.element.scroll(axes);
+        self
+    }
+
+    pub fn wrap(mut self, wrap: FlexWrap) -> Self {
+        self.element = self.element.wrap(wrap);
+        self
+    }
+
+    pub fn grow(mut self, grow: f32) -> Self {
+        self.element = self.element.grow(grow);
+        self
+    }
+
+    pub fn shrink(mut self, shrink: f32) -> Self {
+        self.element = self.element.shrink(shrink);
+        self
+    }
+
+    pub fn basis(mut self, basis: Length) -> Self {
+        self.element = self.element.basis(basis);
+        self
+    }
+
     pub fn color(mut self,color:Srgba) -> Self {
         self.element =  self.element.color(color);
         self
@@ -78,6 +177,11 @@ where
         self
     }
 
+    pub fn key(mut self,key:&str) -> Self {
+        self.element =  self.element.key(key);
+        self
+    }
+
     pub fn click(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
         self.element =  self.element.click(action);
         self
@@ -88,6 +192,121 @@ where
         self
     }
 
+    pub fn double_click(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.double_click(action);
+        self
+    }
+
+    pub fn on_click_capture(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_click_capture(action);
+        self
+    }
+
+    pub fn on_drag_start(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_drag_start(action);
+        self
+    }
+
+    pub fn on_drag(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_drag(action);
+        self
+    }
+
+    pub fn on_drag_end(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_drag_end(action);
+        self
+    }
+
+    pub fn order(mut self, zorder: i32) -> Self {
+        self.element = self.element.order(zorder);
+        self
+    }
+
+    pub fn selectable(mut self, selectable: bool) -> Self {
+        self.element = self.element.selectable(selectable);
+        self
+    }
+
+    pub fn on_selection_change(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_selection_change(action);
+        self
+    }
+
+    pub fn on_gesture(mut self, action: impl Fn(&mut Element, Gesture, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_gesture(action);
+        self
+    }
+
+    pub fn drag_source<T: std::any::Any + Send + Sync>(mut self, payload: T) -> Self {
+        self.element = self.element.drag_source(payload);
+        self
+    }
+
+    pub fn drop_filter(mut self, filter: impl Fn(&DragPayload) -> bool + Send + Sync + 'static) -> Self {
+        self.element = self.element.drop_filter(filter);
+        self
+    }
+
+    pub fn accepts<T: std::any::Any>(mut self) -> Self {
+        self.element = self.element.accepts::<T>();
+        self
+    }
+
+    pub fn on_drag_enter(mut self, action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_drag_enter(action);
+        self
+    }
+
+    pub fn on_drag_over(mut self, action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_drag_over(action);
+        self
+    }
+
+    pub fn on_drag_leave(mut self, action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_drag_leave(action);
+        self
+    }
+
+    pub fn on_drop(mut self, action: impl Fn(&mut Element, &DragPayload, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_drop(action);
+        self
+    }
+
+    pub fn on_right_click(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_right_click(action);
+        self
+    }
+
+    pub fn on_context_menu<E: UIElement>(mut self, menu: impl Fn() -> E + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_context_menu(menu);
+        self
+    }
+
+    pub fn on_middle_click(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_middle_click(action);
+        self
+    }
+
+    pub fn on_middle_drag(mut self, action: impl Fn(&mut Element,&mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_middle_drag(action);
+        self
+    }
+
+    pub fn on_key_down(mut self, action: impl Fn(&mut Element, &KeyboardInput, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_key_down(action);
+        self
+    }
+
+    pub fn on_key_up(mut self, action: impl Fn(&mut Element, &KeyboardInput, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_key_up(action);
+        self
+    }
+
+    pub fn on_text_input(mut self, action: impl Fn(&mut Element, &str, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static) -> Self {
+        self.element = self.element.on_text_input(action);
+        self
+    }
+
     pub fn direction(mut self, direction:FlexDirection) -> Self {
         self.element = self.element.direction(direction);
         self
@@ -98,6 +317,36 @@ where
         self
     }
 
+    pub fn display(mut self, display: Display) -> Self {
+        self.element = self.element.display(display);
+        self
+    }
+
+    pub fn grid_template_rows(mut self, rows: Vec<GridTrack>) -> Self {
+        self.element = self.element.grid_template_rows(rows);
+        self
+    }
+
+    pub fn grid_template_columns(mut self, columns: Vec<GridTrack>) -> Self {
+        self.element = self.element.grid_template_columns(columns);
+        self
+    }
+
+    pub fn gap(mut self, gap: Vec2) -> Self {
+        self.element = self.element.gap(gap);
+        self
+    }
+
+    pub fn grid_row(mut self, line: i16, span: u16) -> Self {
+        self.element = self.element.grid_row(line, span);
+        self
+    }
+
+    pub fn grid_column(mut self, line: i16, span: u16) -> Self {
+        self.element = self.element.grid_column(line, span);
+        self
+    }
+
     pub fn offset(mut self, offset: Vec3) -> Self {
         self.element = self.element.offset(offset);
         self
@@ -124,6 +373,7 @@ where
     }
 
     pub fn push_to_layout(&self, layout: &mut UILayouts) {
+        layout.begin_batch();
         let node_id = layout.push_element(self.element.clone());
         let mut children = Vec::new();
         self.children.foreach_view(&mut |element| {
@@ -146,6 +396,7 @@ where
             }
             children = new_pairs;
         }
+        layout.commit();
     }
 
     pub fn horizontal_alignment(mut self,align:AlignItems)->Self{
@@ -189,6 +440,11 @@ where
         self.element = self.element.drag_enable(enable);
         self
     }
+
+    pub fn cursor(mut self, icon: CursorIcon) -> Self {
+        self.element = self.element.cursor(icon);
+        self
+    }
 }
 
 impl<K> UIElement for Stack<K>
@@ -303,6 +559,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_grid_style() {
+        let style = grid((element(), element().grid_column(1, 2)))
+            .grid_template_columns(vec![GridTrack::Length(100.), GridTrack::minmax(GridTrack::Auto, GridTrack::Fr(1.))])
+            .gap(Vec2::new(4., 8.))
+            .style();
+        assert_eq!(style.display, taffy::Display::Grid);
+        assert_eq!(style.grid_template_columns.len(), 2);
+        assert_eq!(style.gap.width, taffy::LengthPercentage::Length(4.));
+    }
+
     #[test]
     fn test_element_tuple() {
         (
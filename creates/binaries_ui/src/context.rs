@@ -3,7 +3,7 @@ use std::sync::{Arc, RwLock};
 use bevy::{input::ButtonState, math::Vec2, prelude::Resource, utils::HashMap};
 use idgenerator::{IdGeneratorOptions, IdInstance};
 
-use crate::{components::UIMouseState, storage::Node};
+use crate::{components::UIMouseState, drag_drop::DragPayload, inspector::InspectorField, storage::Node};
 
 
 
@@ -19,14 +19,30 @@ pub struct MemState{
     pub drag_delta: (Vec2,Vec2,Vec2),
     pub mouse_position:Vec2,
     pub mouse_delta: Vec2,
+    /// Element id shown in the inspector, -1 when closed.
+    pub inspected: i64,
+    pub inspector_edits: Vec<(InspectorField, f32)>,
+    /// Element id holding the keyboard focus, -1 when none.
+    pub focused: i64,
+    /// Seconds since startup, set every frame.
+    pub time: f64,
+    /// Time and position of the last click, for double click detection.
+    pub last_click: (f64, Vec2),
+    pub double_click_time: f64,
+    pub double_click_distance: f32,
+    /// Distance the pressed cursor must move before a press turns into a drag.
+    pub drag_threshold: f32,
+    /// Cursor movement of the current middle button drag since its last callback.
+    pub pan_delta: Vec2,
+    /// Payload of the drag and drop in progress.
+    pub drag_payload: Option<DragPayload>,
+    propagation_stopped: bool,
+    default_prevented: bool,
 }
 
-impl Default for Context {
+impl Default for MemState {
     fn default() -> Self {
-        let options = IdGeneratorOptions::new().worker_id(1).worker_id_bit_len(6);
-        let _ = IdInstance::init(options).expect("IdInstance init error");
-
-        Self(Arc::new(RwLock::new(MemState{
+        Self{
             node: HashMap::new(),
             mouse_state: ButtonState::Released,
             user_input: UIMouseState::Release,
@@ -35,7 +51,74 @@ impl Default for Context {
             drag_delta: (Vec2::ZERO,Vec2::ZERO,Vec2::ZERO),
             mouse_position: Vec2::ONE * -100.,
             mouse_delta: Vec2::ZERO,
-        })))
+            inspected: -1,
+            inspector_edits: Vec::new(),
+            focused: -1,
+            time: 0.,
+            last_click: (f64::NEG_INFINITY, Vec2::ZERO),
+            double_click_time: 0.4,
+            double_click_distance: 4.,
+            drag_threshold: 4.,
+            pan_delta: Vec2::ZERO,
+            drag_payload: None,
+            propagation_stopped: false,
+            default_prevented: false,
+        }
+    }
+}
+
+impl MemState {
+    /// Keep the left click, double click or gesture being dispatched from reaching the next
+    /// elements of its path. Other pointer callbacks never propagate.
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    /// Skip the built-in reaction to a click: opening the context menu from a right click
+    /// callback, or closing the context menu from a click inside it. Clicks have no other one.
+    /// From a key callback of the focused element, keeps the key from reaching the shortcuts.
+    pub fn prevent_default(&mut self) {
+        self.default_prevented = true;
+    }
+
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.propagation_stopped
+    }
+
+    pub fn is_default_prevented(&self) -> bool {
+        self.default_prevented
+    }
+
+    pub(crate) fn begin_event(&mut self) {
+        self.propagation_stopped = false;
+        self.default_prevented = false;
+    }
+
+    /// The pressed cursor moved far enough to be a drag rather than a click.
+    pub fn is_dragging(&self) -> bool {
+        (self.drag_delta.1 - self.drag_delta.0).length() > self.drag_threshold
+    }
+
+    /// Record a click at the cursor and tell whether it completes a double click.
+    pub fn register_click(&mut self) -> bool {
+        let position = self.mouse_position;
+        let double = self.time - self.last_click.0 <= self.double_click_time
+            && position.distance(self.last_click.1) <= self.double_click_distance;
+        // a third click starts a new double click
+        self.last_click = match double {
+            true => (f64::NEG_INFINITY, position),
+            false => (self.time, position),
+        };
+        double
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        let options = IdGeneratorOptions::new().worker_id(1).worker_id_bit_len(6);
+        let _ = IdInstance::init(options).expect("IdInstance init error");
+
+        Self(Arc::new(RwLock::new(MemState::default())))
     }
 }
 
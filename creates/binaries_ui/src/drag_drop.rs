@@ -0,0 +1,56 @@
+use std::{any::Any, sync::Arc};
+
+use bevy::math::Vec2;
+use taffy::NodeId;
+
+/// Value carried by a drag and drop, drop targets read it back by type.
+#[derive(Clone)]
+pub struct DragPayload(Arc<dyn Any + Send + Sync>);
+
+impl DragPayload {
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum DropEvent {
+    Enter,
+    Over,
+    Leave,
+    Drop,
+}
+
+/// A drag and drop in progress from `source`.
+pub(crate) struct DragSession {
+    pub(crate) source: NodeId,
+    pub(crate) payload: DragPayload,
+    /// Drop target under the cursor and whether it accepts the payload.
+    pub(crate) target: Option<(NodeId, bool)>,
+    /// Where the ghost of the source is drawn, in painter space.
+    pub(crate) ghost: Vec2,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload() {
+        let payload = DragPayload::new(String::from("card"));
+        assert!(payload.is::<String>());
+        assert!(!payload.is::<u32>());
+        assert_eq!(payload.get::<String>().map(String::as_str), Some("card"));
+        assert_eq!(payload.get::<u32>(), None);
+        // clones share the value
+        assert_eq!(payload.clone().get::<String>(), payload.get::<String>());
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::{input::mouse::{MouseScrollUnit, MouseWheel}, math::Vec2};
+
+/// Zoom factor change of one notch of Ctrl + wheel.
+const PINCH_PER_LINE: f32 = 0.1;
+/// Zoom factor change of one pixel of Ctrl + trackpad scroll.
+const PINCH_PER_PIXEL: f32 = 0.005;
+/// Radians turned by one pixel of an Alt + drag.
+const ROTATE_PER_PIXEL: f32 = 0.01;
+/// Pixels scrolled by one notch of a mouse wheel.
+const SCROLL_LINE_HEIGHT: f32 = 20.;
+
+/// Gesture sent to the element under the cursor, then up its parent chain.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Gesture {
+    /// Change of the zoom factor, positive zooms in.
+    Pinch(f32),
+    /// Rotation in radians, positive is counterclockwise.
+    Rotate(f32),
+    DoubleTap,
+    /// Movement of the content in window pixels, y pointing down, from two-finger scroll,
+    /// a two-finger touch drag or a pan drag.
+    Pan(Vec2),
+}
+
+/// Mouse drag synthesizing a gesture where there is no trackpad.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GestureDrag {
+    /// Middle button drag that no element handles.
+    Pan,
+    /// Alt + left drag, turning with the horizontal movement.
+    Rotate,
+}
+
+impl GestureDrag {
+    pub(crate) fn gesture(&self, movement: Vec2) -> Gesture {
+        match self {
+            GestureDrag::Pan => Gesture::Pan(movement),
+            GestureDrag::Rotate => Gesture::Rotate(-movement.x * ROTATE_PER_PIXEL),
+        }
+    }
+}
+
+/// Ctrl + wheel is a pinch and trackpad scrolling, in pixels, a pan.
+/// Line scrolling of a mouse wheel stays a plain scroll.
+pub fn wheel_gesture(event: &MouseWheel, ctrl: bool) -> Option<Gesture> {
+    match (ctrl, event.unit) {
+        (true, MouseScrollUnit::Line) => Some(Gesture::Pinch(event.y * PINCH_PER_LINE)),
+        (true, MouseScrollUnit::Pixel) => Some(Gesture::Pinch(event.y * PINCH_PER_PIXEL)),
+        (false, MouseScrollUnit::Pixel) => Some(Gesture::Pan(wheel_movement(event))),
+        (false, MouseScrollUnit::Line) => None,
+    }
+}
+
+/// Movement of the content asked by a wheel event, in window pixels with y pointing down like
+/// `Gesture::Pan`. Winit deltas are positive when the content moves right and down, a scroll
+/// view moves its scroll offset by the opposite.
+pub(crate) fn wheel_movement(event: &MouseWheel) -> Vec2 {
+    let scale = match event.unit {
+        MouseScrollUnit::Line => SCROLL_LINE_HEIGHT,
+        MouseScrollUnit::Pixel => 1.,
+    };
+    Vec2::new(event.x, event.y) * scale
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Entity;
+
+    use super::*;
+
+    #[test]
+    fn test_wheel_gesture() {
+        let wheel = |unit, y| MouseWheel { unit, x: 0., y, window: Entity::PLACEHOLDER };
+        assert_eq!(wheel_gesture(&wheel(MouseScrollUnit::Line, 2.), true), Some(Gesture::Pinch(0.2)));
+        assert_eq!(wheel_gesture(&wheel(MouseScrollUnit::Line, 2.), false), None);
+        assert_eq!(wheel_gesture(&wheel(MouseScrollUnit::Pixel, 6.), false), Some(Gesture::Pan(Vec2::new(0., 6.))));
+        assert_eq!(wheel_movement(&wheel(MouseScrollUnit::Line, -2.)), Vec2::new(0., -40.));
+    }
+}
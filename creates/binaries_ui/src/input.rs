@@ -1,7 +1,7 @@
-use bevy::{ input::{keyboard::KeyboardInput, mouse::{MouseButtonInput, MouseMotion}, ButtonState}, log::info, math::{Vec2, Vec3}, prelude::{Commands, EventReader, KeyCode, MouseButton, ResMut}, window::CursorMoved};
+use bevy::{ input::{gestures::{DoubleTapGesture, PanGesture, PinchGesture, RotationGesture}, keyboard::KeyboardInput, mouse::{MouseButtonInput, MouseMotion, MouseWheel}, touch::TouchInput, ButtonInput, ButtonState}, log::info, math::{Vec2, Vec3}, prelude::{Commands, EventReader, KeyCode, MouseButton, Res, ResMut}, window::CursorMoved};
 use bevy_vector_shapes::prelude::ShapePainter;
 
-use crate::{components::UIMouseState, layout::UILayouts, Config};
+use crate::{gesture::{Gesture, GestureDrag}, layout::UILayouts, Config};
 
 pub fn logic_loop_system(
     mut painter: ShapePainter,
@@ -10,11 +10,14 @@ pub fn logic_loop_system(
     mut mouse_button_input_events: EventReader<MouseButtonInput>,
     mut mouse_motion_events: EventReader<MouseMotion>,
     mut keyboard_input_evets: EventReader<KeyboardInput>,
-    // mut mouse_wheel_events: EventReader<MouseWheel>,
-    // mut pinch_gesture_events: EventReader<PinchGesture>,
-    // mut rotation_gesture_events: EventReader<RotationGesture>,
-    // mut double_tap_gesture_events: EventReader<DoubleTapGesture>,
-    config: Config,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut pinch_gesture_events: EventReader<PinchGesture>,
+    mut rotation_gesture_events: EventReader<RotationGesture>,
+    mut double_tap_gesture_events: EventReader<DoubleTapGesture>,
+    mut pan_gesture_events: EventReader<PanGesture>,
+    mut touch_events: EventReader<TouchInput>,
+    mut config: Config,
     commands: Commands,
 ) {
     let window = config.window.get_single().unwrap();
@@ -27,6 +30,7 @@ pub fn logic_loop_system(
         return;
     }
 
+    cxt.time = config.time.elapsed_seconds_f64();
     cxt.mouse_delta = Vec2::ZERO;
     for event in cursor_moved_events.read() {
         cxt.mouse_position = event.position;
@@ -47,52 +51,54 @@ pub fn logic_loop_system(
                 state: ButtonState::Pressed,
                 ..
             } => {
-                layouts.update_input_state(UIMouseState::Pressed);
-                cxt.drag_delta.0 = cxt.mouse_position.clone();
-                cxt.drag_delta.1 = cxt.mouse_position.clone();
-                cxt.mouse_state = ButtonState::Pressed;
+                let cursor = cxt.mouse_position;
+                layouts.pointer_pressed(&mut cxt, cursor, &keys);
             }
             MouseButtonInput {
                 button: MouseButton::Left,
                 state: ButtonState::Released,
                 ..
             } => {
-                layouts.update_input_state(UIMouseState::Release);
-                cxt.drag_delta.2 = cxt.drag_delta.1 - cxt.drag_delta.0;
-                cxt.drag_delta.0 = cxt.mouse_position.clone();
-                cxt.drag_delta.1 = cxt.mouse_position.clone(); 
-                cxt.mouse_state = ButtonState::Released;
+                layouts.pointer_released(&mut cxt);
             }
-            _ => {}
-         }
-    }
-
-    for event in keyboard_input_evets.read() {
-        match event {
-            KeyboardInput{
-                key_code:KeyCode::KeyM,
-                state:ButtonState::Pressed,
+            MouseButtonInput {
+                button,
+                state: ButtonState::Pressed,
                 ..
-            }=>{
-                println!("ctx {:?}", cxt.user_input);
+            } => {
+                // a middle drag that no element takes pans
+                if !layouts.press_button(*button, cxt.mouse_position) && *button == MouseButton::Middle {
+                    layouts.begin_gesture_drag(GestureDrag::Pan, cxt.mouse_position);
+                }
             }
-            KeyboardInput{
-                key_code:KeyCode::Escape,
-                state:ButtonState::Pressed,
+            MouseButtonInput {
+                button,
+                state: ButtonState::Released,
                 ..
-            }=>{
-                cxt.user_input = UIMouseState::Release;
-                println!("ctx {:?}", cxt.user_input);
+            } => {
+                let cursor = cxt.mouse_position;
+                layouts.release_button(&mut cxt, *button, cursor);
+                if *button == MouseButton::Middle {
+                    layouts.end_gesture_drag();
+                }
             }
-            _=>{}
-        }
+         }
+    }
+
+    for event in touch_events.read() {
+        layouts.touch_input(&mut cxt, event, &keys);
+    }
+
+    for event in keyboard_input_evets.read() {
+        layouts.key_input(&mut cxt, event, &keys);
     }
 
     for event in mouse_motion_events.read() {
         info!("{:?} {:?}", event,cxt.mouse_position);
         match cxt.mouse_state {
             ButtonState::Pressed => {
-                cxt.drag_delta.1 = cxt.mouse_position;
+                let cursor = cxt.mouse_position;
+                layouts.pointer_moved(&mut cxt, cursor);
             },
             ButtonState::Released => {
                 // cxt.drag_delta.0 = Vec2::ZERO;
@@ -101,27 +107,38 @@ pub fn logic_loop_system(
         }
     }
 
-    layouts.update(&mut cxt, painter.origin.unwrap());
-    layouts.update_shape(config, commands);
-
-    // for event in mouse_wheel_events.read() {
-    //     // info!("{:?}", event);
-    // }
+    let cursor = cxt.mouse_position;
+    layouts.drag_buttons(&mut cxt, cursor);
+    layouts.drag_gesture(&mut cxt, cursor);
 
-    // // This event will only fire on macOS
-    // for event in pinch_gesture_events.read() {
-    //     info!("{:?}", event);
-    // }
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    for event in mouse_wheel_events.read() {
+        layouts.wheel(&mut cxt, cursor, event, ctrl);
+    }
 
-    // // This event will only fire on macOS
-    // for event in rotation_gesture_events.read() {
-    //     // info!("{:?}", event);
-    // }
+    // native gestures, only sent by macOS and iOS trackpads and screens
+    for event in pinch_gesture_events.read() {
+        layouts.gesture(&mut cxt, cursor, Gesture::Pinch(event.0));
+    }
+    for event in rotation_gesture_events.read() {
+        layouts.gesture(&mut cxt, cursor, Gesture::Rotate(event.0));
+    }
+    for _ in double_tap_gesture_events.read() {
+        layouts.gesture(&mut cxt, cursor, Gesture::DoubleTap);
+    }
+    for event in pan_gesture_events.read() {
+        layouts.gesture(&mut cxt, cursor, Gesture::Pan(event.0));
+    }
 
-    // This event will only fire on macOS
-    // for event in double_tap_gesture_events.read() {
-    //     // layouts.update_input_state(UIMouse::Click);
-    // }
+    layouts.update(&mut cxt, painter.origin.unwrap());
+    let cursor = layouts.cursor();
+    if let Ok(mut window) = config.window.get_single_mut() {
+        // only write on change, the window is updated on every change detected
+        if window.cursor.icon != cursor {
+            window.cursor.icon = cursor;
+        }
+    }
+    layouts.update_shape(config, commands);
 
     layouts.draw(&mut painter);
     layouts.exc_action(&mut cxt);
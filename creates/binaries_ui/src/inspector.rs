@@ -0,0 +1,180 @@
+use bevy::{color::{palettes::tailwind::{GRAY_500, GRAY_700}, Alpha, Hsla, Hue, Srgba}, math::{Vec2, Vec4}};
+use taffy::NodeId;
+
+use crate::{components::{element, element::{AlignItems, ElementType, FlexDirection, Length}, rectangle, text}, layout::UILayouts, traits::UIElement};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InspectorField {
+    Width,
+    Height,
+    Margin,
+    Padding,
+    Round,
+    ZOrder,
+    Color(ColorChannel),
+    BackgroundColor(ColorChannel),
+}
+
+impl InspectorField {
+    pub fn step(&self) -> f32 {
+        match self {
+            InspectorField::Width | InspectorField::Height => 5.,
+            InspectorField::Color(channel) | InspectorField::BackgroundColor(channel) => channel.step(),
+            _ => 1.,
+        }
+    }
+}
+
+/// Part of a color edited from the inspector, in HSL so grays and white can be tinted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorChannel {
+    /// Hue in degrees, wrapping around.
+    Hue,
+    Saturation,
+    Lightness,
+    Alpha,
+}
+
+impl ColorChannel {
+    pub fn step(&self) -> f32 {
+        match self {
+            ColorChannel::Hue => 30.,
+            _ => 0.1,
+        }
+    }
+
+    /// `color` with this channel moved by `delta`, clamped to its range.
+    pub(crate) fn apply(&self, color: Srgba, delta: f32) -> Srgba {
+        let hsla = Hsla::from(color);
+        let hsla = match self {
+            ColorChannel::Hue => hsla.rotate_hue(delta),
+            ColorChannel::Saturation => hsla.with_saturation((hsla.saturation + delta).clamp(0., 1.)),
+            ColorChannel::Lightness => hsla.with_lightness((hsla.lightness + delta).clamp(0., 1.)),
+            ColorChannel::Alpha => hsla.with_alpha((hsla.alpha + delta).clamp(0., 1.)),
+        };
+        hsla.into()
+    }
+}
+
+/// Panel listing the computed layout and the properties of the inspected element.
+pub(crate) struct Inspector {
+    pub(crate) target: NodeId,
+    pub(crate) panel: NodeId,
+    rows: Vec<(NodeId, String)>,
+}
+
+impl Inspector {
+    pub(crate) fn open(layouts: &mut UILayouts, target: NodeId) -> Self {
+        let root = layouts.root;
+        layouts.begin_batch();
+        let panel = layouts.push_element_with_id(
+            rectangle()
+                .round(5.)
+                .color(GRAY_700)
+                .padding(Vec4::splat(5.))
+                .direction(FlexDirection::Column)
+                .element_type(ElementType::Inspector)
+                .title("inspector"),
+            root,
+        );
+
+        let mut rows = Vec::new();
+        for (line, field) in inspector_lines(layouts, target).unwrap_or_default() {
+            let row = layouts.push_element_with_id(element().title("inspector row").vertical_alignment(AlignItems::Center), panel);
+            let label = layouts.push_element_with_id(text(&line).size(Vec2::new(300., 16.)).margin(Vec4::new(0., 2., 0., 2.)), row);
+            rows.push((label, line));
+
+            if let Some(field) = field {
+                for delta in [-field.step(), field.step()] {
+                    let button = rectangle()
+                        .size(Vec2::new(20., 16.))
+                        .round(3.)
+                        .color(GRAY_500)
+                        .margin(Vec4::new(4., 2., 0., 2.))
+                        .click(move |_, cxt| cxt.inspector_edits.push((field, delta)));
+                    let button = layouts.push_element_with_id(button, row);
+                    let sign = if delta < 0. { "-" } else { "+" };
+                    layouts.push_element_with_id(text(sign).size(Vec2::new(10., 16.)), button);
+                }
+            }
+        }
+
+        let close = rectangle()
+            .size(Vec2::new(60., 16.))
+            .round(3.)
+            .color(GRAY_500)
+            .margin(Vec4::new(0., 4., 0., 0.))
+            .click(|_, cxt| cxt.inspected = -1);
+        let close = layouts.push_element_with_id(close, panel);
+        layouts.push_element_with_id(text("close").size(Vec2::new(40., 16.)), close);
+        layouts.commit();
+
+        Self { target, panel, rows }
+    }
+
+    /// Update the rows whose text changed since the last frame, false once the target is gone.
+    pub(crate) fn refresh(&mut self, layouts: &UILayouts) -> bool {
+        let Some(lines) = inspector_lines(layouts, self.target) else {
+            return false;
+        };
+        for ((label, content), (line, _)) in self.rows.iter_mut().zip(lines) {
+            if *content != line {
+                if let Some(shape) = layouts.element(*label).and_then(|element| element.shape.as_ref()) {
+                    shape.write().unwrap().set_text(&line);
+                }
+                *content = line;
+            }
+        }
+        true
+    }
+}
+
+/// Rows of the panel, `None` when `target` was removed.
+fn inspector_lines(layouts: &UILayouts, target: NodeId) -> Option<Vec<(String, Option<InspectorField>)>> {
+    let element = layouts.element(target)?;
+    let layout = layouts.taffy.layout(target).ok()?;
+    let style = layouts.taffy.style(target).ok()?;
+    let margin = element.get_margin();
+    let padding = element.get_padding();
+    let dimension = |length: Length| match length {
+        Length::Auto => "auto".to_string(),
+        Length::Px(value) => format!("{:.1}", value),
+        Length::Percent(value) => format!("{:.0}%", value * 100.),
+    };
+
+    let mut lines = vec![
+        (format!("title: {}", element.get_title()), None),
+        (format!("location: {:.1}, {:.1}", layout.location.x, layout.location.y), None),
+        (format!("size: {:.1} x {:.1}", layout.size.width, layout.size.height), None),
+        (format!("content size: {:.1} x {:.1}", layout.content_size.width, layout.content_size.height), None),
+        (format!("width: {}", dimension(element.get_width())), Some(InspectorField::Width)),
+        (format!("height: {}", dimension(element.get_height())), Some(InspectorField::Height)),
+        (format!("display: {:?} position: {:?}", style.display, style.position), None),
+        (format!("direction: {:?} wrap: {:?}", style.flex_direction, style.flex_wrap), None),
+        (format!("grow: {} shrink: {} basis: {:?}", style.flex_grow, style.flex_shrink, style.flex_basis), None),
+        (format!("justify content: {:?}", style.justify_content), None),
+        (format!("align items: {:?} self: {:?}", style.align_items, style.align_self), None),
+    ];
+    lines.extend(color_lines("color", element.get_color(), InspectorField::Color));
+    lines.extend(color_lines("background", element.get_background_color(), InspectorField::BackgroundColor));
+    lines.extend([
+        (format!("margin: {:.0} {:.0} {:.0} {:.0}", margin.x, margin.y, margin.z, margin.w), Some(InspectorField::Margin)),
+        (format!("padding: {:.0} {:.0} {:.0} {:.0}", padding.x, padding.y, padding.z, padding.w), Some(InspectorField::Padding)),
+        (format!("round: {:.0}", element.get_round().x), Some(InspectorField::Round)),
+        (format!("z-order: {}", element.get_z_order()), Some(InspectorField::ZOrder)),
+        (format!("action: {:?} render: {:?}", element.action_state, element.render_state), None),
+    ]);
+    Some(lines)
+}
+
+/// Hex value of `color`, then a row per channel with its buttons.
+fn color_lines(label: &str, color: Srgba, field: fn(ColorChannel) -> InspectorField) -> Vec<(String, Option<InspectorField>)> {
+    let hsla = Hsla::from(color);
+    vec![
+        (format!("{}: {}", label, color.to_hex()), None),
+        (format!("  hue: {:.0}", hsla.hue), Some(field(ColorChannel::Hue))),
+        (format!("  saturation: {:.1}", hsla.saturation), Some(field(ColorChannel::Saturation))),
+        (format!("  lightness: {:.1}", hsla.lightness), Some(field(ColorChannel::Lightness))),
+        (format!("  alpha: {:.1}", hsla.alpha), Some(field(ColorChannel::Alpha))),
+    ]
+}
@@ -1,22 +1,87 @@
-use std::{collections::HashMap, sync::RwLockWriteGuard};
+use std::{collections::{HashMap, HashSet}, sync::{Arc, OnceLock, RwLockWriteGuard}};
 
-use bevy::{color::palettes::{css::{BLACK, GREEN}, tailwind::{GREEN_200, RED_400}}, math::{Vec2, Vec3, Vec4}, prelude::{Commands, Resource}};
+use bevy::{color::palettes::{css::{BLACK, GREEN}, tailwind::{GREEN_200, RED_400}}, input::{keyboard::{Key, KeyboardInput}, mouse::{MouseButton, MouseWheel}, touch::{TouchInput, TouchPhase}, ButtonInput, ButtonState}, log::warn, math::{Rect, Vec2, Vec3, Vec4}, prelude::{Commands, KeyCode, Resource}, window::CursorIcon};
 use bevy_vector_shapes::prelude::ShapePainter;
 use taffy::{
-    prelude::TaffyMaxContent, Dimension, JustifyContent, NodeId, Size, Style, TaffyTree, TraversePartialTree
+    prelude::TaffyMaxContent, Dimension, Display, JustifyContent, NodeId, Size, Style, TaffyError, TaffyResult, TaffyTree, TraversePartialTree
 };
 
-use crate::{components::{element::{AlignItems, Element, FlexDirection}, rectangle, text, UIMouseState}, context::MemState, shape::{Curve, ShapeTrait}, Config};
+use crate::{components::{element, element::{AlignItems, Clip, Element, FlexDirection, Length, Position, Shape, DEPTH_STEP}, rectangle, text, UIMouseState}, context::MemState, drag_drop::{DragSession, DropEvent}, gesture::{wheel_gesture, wheel_movement, Gesture, GestureDrag}, inspector::{Inspector, InspectorField}, overlay, selection::{align, GroupDrag, RubberBand, SelectMode}, shape::{Curve, ShapeTrait, Text}, shortcut::{Shortcut, ShortcutId, Shortcuts}, touch::Touches, Config};
 
 use super::traits::UIElement;
 
+/// Stable reference to an element, it stays valid when the element is removed and pushed again.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ElementHandle(i64);
+
+impl ElementHandle {
+    pub fn id(&self) -> i64 {
+        self.0
+    }
+}
+
+/// A debug tree panel, mirrors the subtree under `target` into the panel node.
+pub struct DebugView {
+    pub target: NodeId,
+    pub visible: bool,
+    debuge_relations: HashMap<NodeId, NodeId>,
+    // children of each mirrored node when its mirror was generated
+    mirrored_children: HashMap<NodeId, Vec<NodeId>>,
+    version: u64,
+}
+
+/// Rows built so far for a virtual list, by row index.
+struct VirtualList {
+    spacer: NodeId,
+    rows: HashMap<usize, NodeId>,
+}
+
+/// A right or middle button held since it was pressed on `node`.
+struct ButtonPress {
+    node: NodeId,
+    start: Vec2,
+    last: Vec2,
+    dragged: bool,
+}
+
+/// Rows built above and below the visible ones of a virtual list.
+const VIRTUAL_OVERSCAN: usize = 2;
+
 #[derive(Resource)]
 pub struct UILayouts {
     elements: HashMap<NodeId, Element>,
-    debuge_relations: HashMap<NodeId, NodeId>,
+    debug_views: HashMap<NodeId, DebugView>,
+    virtual_lists: HashMap<NodeId, VirtualList>,
+    shortcuts: Shortcuts,
+    handles: HashMap<ElementHandle, NodeId>,
+    keys: HashMap<String, ElementHandle>,
+    restyles: HashSet<NodeId>,
+    tree_version: u64,
+    /// Visible nodes in the order they are drawn, until the tree, a style or a z-order changes.
+    paint_order: OnceLock<Vec<NodeId>>,
+    inspector: Option<Inspector>,
+    removed_shapes: Vec<Shape>,
+    bounds_overlay: bool,
+    overlay_labels: HashMap<NodeId, Text>,
     pub taffy: TaffyTree<()>,
     pub root: NodeId,
-    pub debug_root: NodeId
+    window_size: Vec2,
+    /// Scroll view whose thumb is dragged, its axis and the last cursor position.
+    scroll_drag: Option<(NodeId, bool, Vec2)>,
+    buttons: HashMap<MouseButton, ButtonPress>,
+    context_menu: Option<NodeId>,
+    drag_drop: Option<DragSession>,
+    rubber_band: Option<RubberBand>,
+    group_drag: Option<GroupDrag>,
+    /// Mouse drag synthesizing a gesture, with the press position and the last cursor position.
+    gesture_drag: Option<(GestureDrag, Vec2, Vec2)>,
+    /// Cursor of the window asked by the hovered elements.
+    cursor: CursorIcon,
+    touches: Touches,
+    snap_grid: Option<f32>,
+    alignment_guides: bool,
+    batch_depth: usize,
+    dirty: bool,
 }
 
 impl UILayouts {
@@ -30,114 +95,669 @@ impl UILayouts {
                 },
             ).expect("");
         let mut elements = HashMap::new();
-        elements.insert(node, Element::new().title("root").background_color(GREEN));
-        Self {
+        let root = Element::new().title("root").background_color(GREEN);
+        let mut handles = HashMap::new();
+        handles.insert(ElementHandle(root.id()), node);
+        elements.insert(node, root);
+        let mut layouts = Self {
             taffy,
             elements: elements,
+            handles,
+            keys: HashMap::new(),
+            restyles: HashSet::new(),
+            tree_version: 0,
+            paint_order: OnceLock::new(),
+            inspector: None,
+            removed_shapes: Vec::new(),
+            bounds_overlay: false,
+            overlay_labels: HashMap::new(),
             root: node,
-            debug_root: NodeId::new(0u64),
-            debuge_relations: HashMap::new(),
+            debug_views: HashMap::new(),
+            virtual_lists: HashMap::new(),
+            shortcuts: Shortcuts::default(),
+            window_size: Vec2::ZERO,
+            scroll_drag: None,
+            buttons: HashMap::new(),
+            context_menu: None,
+            drag_drop: None,
+            rubber_band: None,
+            group_drag: None,
+            gesture_drag: None,
+            cursor: CursorIcon::Default,
+            touches: Touches::default(),
+            snap_grid: None,
+            alignment_guides: false,
+            batch_depth: 0,
+            dirty: true,
+        };
+        layouts.add_default_shortcuts();
+        layouts
+    }
+
+    fn add_default_shortcuts(&mut self) {
+        self.add_shortcut(Shortcut::new(KeyCode::Tab), None, |layouts, cxt| layouts.focus_next(cxt, false));
+        self.add_shortcut(Shortcut::new(KeyCode::Tab).shift(), None, |layouts, cxt| layouts.focus_next(cxt, true));
+        self.add_shortcut(Shortcut::new(KeyCode::Enter), None, |layouts, cxt| layouts.activate_focused(cxt));
+        self.add_shortcut(Shortcut::new(KeyCode::Space), None, |layouts, cxt| layouts.activate_focused(cxt));
+    }
+
+    /// Opt in debug keys: B toggles the bounds overlay.
+    pub fn add_debug_shortcuts(&mut self) {
+        self.add_shortcut(Shortcut::new(KeyCode::KeyB), None, |layouts, _| {
+            let enable = !layouts.bounds_overlay();
+            layouts.set_bounds_overlay(enable);
+        });
+    }
+
+    /// Run `action` when `shortcut` is pressed, only while the focus is inside `scope` when given.
+    pub fn add_shortcut(
+        &mut self,
+        shortcut: Shortcut,
+        scope: Option<NodeId>,
+        action: impl Fn(&mut UILayouts, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static,
+    ) -> ShortcutId {
+        self.shortcuts.add(shortcut, scope, Arc::new(action))
+    }
+
+    pub fn remove_shortcut(&mut self, id: ShortcutId) {
+        self.shortcuts.remove(id);
+    }
+
+    /// Route a key event to the key callbacks of the focused element first, then to the
+    /// shortcuts unless a callback prevented the default, like Enter activating the element.
+    /// Keys typing text into a focused text input skip the shortcuts.
+    pub fn key_input(&mut self, cxt: &mut RwLockWriteGuard<MemState>, event: &KeyboardInput, keys: &ButtonInput<KeyCode>) {
+        // Escape during a group drag only cancels it
+        if event.state == ButtonState::Pressed && event.key_code == KeyCode::Escape && self.is_group_dragging() {
+            self.cancel_group_drag();
+            return;
+        }
+        let focused = self.focused(cxt);
+        let shortcut = Shortcut::pressed(event.key_code, keys);
+        let text = match &event.logical_key {
+            Key::Character(text) if !shortcut.ctrl && !shortcut.alt => Some(text.to_string()),
+            Key::Space if !shortcut.ctrl && !shortcut.alt => Some(" ".to_string()),
+            _ => None,
+        };
+        let typing = text.is_some() && focused.is_some_and(|node| self.elements[&node].accepts_text());
+
+        cxt.begin_event();
+        if let Some(element) = focused.and_then(|node| self.elements.get_mut(&node)) {
+            element.key_input(event, cxt);
+        }
+        if event.state == ButtonState::Released || cxt.is_default_prevented() {
+            return;
+        }
+        if !typing {
+            let path = focused.map_or(Vec::new(), |node| self.ancestors(node));
+            if let Some(action) = self.shortcuts.find(shortcut, &path) {
+                action(self, cxt);
+                return;
+            }
+        }
+        if let (Some(element), Some(text)) = (focused.and_then(|node| self.elements.get_mut(&node)), text) {
+            element.text_input(&text, cxt);
         }
     }
+
+    /// `node` followed by its ancestors up to the root.
+    pub(crate) fn ancestors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut path = vec![node];
+        while let Some(parent) = self.taffy.parent(*path.last().unwrap()) {
+            path.push(parent);
+        }
+        path
+    }
     
     pub fn clear_node(&mut self, node: NodeId){
+        self.remove_element(node);
+    }
+
+    /// Remove `node` and its whole subtree, together with their elements and debug relations.
+    /// Returns the element of `node` carrying its descendants, pushing it again rebuilds the
+    /// subtree with the same `ElementHandle`s and keys. Rows of virtual lists and mirrors of
+    /// debug views are left out, they are generated again.
+    pub fn remove_element(&mut self, node: NodeId) -> Option<Element> {
+        if node == self.root || !self.elements.contains_key(&node) {
+            return None;
+        }
+        let mut retarget = Vec::new();
         let mut nodes_to_remove = Vec::new();
         self.traverse_node(node, &mut nodes_to_remove);
-        for node_to_remove in nodes_to_remove{
-            self.taffy.remove(node_to_remove).expect("node_to_remove");
+        let children: HashMap<NodeId, Vec<NodeId>> = nodes_to_remove
+            .iter()
+            .filter(|node| !self.virtual_lists.contains_key(node) && !self.debug_views.contains_key(node))
+            .map(|node| (*node, self.taffy.children(*node).expect("remove_element")))
+            .collect();
+        let nodes_to_remove: HashSet<NodeId> = nodes_to_remove.into_iter().collect();
+        let mut removed = HashMap::new();
+        for node_to_remove in nodes_to_remove.iter() {
+            self.taffy.remove(*node_to_remove).expect("node_to_remove");
+            self.restyles.remove(node_to_remove);
+            if let Some(element) = self.elements.remove(node_to_remove) {
+                self.unregister(&element);
+                if let Some(shape) = element.shape.as_ref() {
+                    self.removed_shapes.push(shape.clone());
+                }
+                removed.insert(*node_to_remove, element);
+            }
+            self.debug_views.remove(node_to_remove);
+            self.virtual_lists.remove(node_to_remove);
         }
+        for list in self.virtual_lists.values_mut() {
+            list.rows.retain(|_, row| !nodes_to_remove.contains(row));
+        }
+        self.shortcuts.remove_scopes(|scope| nodes_to_remove.contains(&scope));
+        self.buttons.retain(|_, press| !nodes_to_remove.contains(&press.node));
+        if self.context_menu.is_some_and(|menu| nodes_to_remove.contains(&menu)) {
+            self.context_menu = None;
+        }
+        for (panel, view) in self.debug_views.iter_mut() {
+            view.debuge_relations.retain(|element, debug_element| {
+                !nodes_to_remove.contains(element) && !nodes_to_remove.contains(debug_element)
+            });
+            view.mirrored_children.retain(|element, _| !nodes_to_remove.contains(element));
+            if nodes_to_remove.contains(&view.target) {
+                retarget.push(*panel);
+            }
+        }
+        for panel in retarget {
+            let root = self.root;
+            self.set_debug_target(panel, root);
+        }
+        self.layout_changed();
+        detach(node, &mut removed, &children)
     }
 
-    pub fn iter(&mut self) -> impl Iterator<Item = &mut Element> {
-        self.elements.values_mut()
+    /// Move `node` under `parent` at `index`, the index is clamped to the child count of `parent`.
+    pub fn move_element(&mut self, node: NodeId, parent: NodeId, index: usize) -> TaffyResult<()> {
+        if node == self.root || !self.elements.contains_key(&node) {
+            return Err(TaffyError::InvalidChildNode(node));
+        }
+        if !self.elements.contains_key(&parent) || self.is_ancestor(node, parent) {
+            return Err(TaffyError::InvalidParentNode(parent));
+        }
+        if let Some(old_parent) = self.taffy.parent(node) {
+            self.taffy.remove_child(old_parent, node)?;
+        }
+        let index = index.min(self.taffy.child_count(parent));
+        self.taffy.insert_child_at_index(parent, index, node)?;
+        self.layout_changed();
+        Ok(())
     }
 
-    ///WIP:olny one debug-node. Need to Update to multi-debug-nodes
-    pub fn push_element(&mut self, element: Element) -> NodeId {
-        let child = self
-            .taffy
-            .new_leaf(element.style()).unwrap();
+    /// Swap the positions of two siblings.
+    pub fn swap_elements(&mut self, a: NodeId, b: NodeId) -> TaffyResult<()> {
+        if !self.elements.contains_key(&a) {
+            return Err(TaffyError::InvalidChildNode(a));
+        }
+        if !self.elements.contains_key(&b) {
+            return Err(TaffyError::InvalidChildNode(b));
+        }
+        let parent = self.taffy.parent(a).ok_or(TaffyError::InvalidChildNode(a))?;
+        if self.taffy.parent(b) != Some(parent) {
+            return Err(TaffyError::InvalidChildNode(b));
+        }
+        let mut children = self.taffy.children(parent)?;
+        let index_a = children.iter().position(|child| *child == a).unwrap();
+        let index_b = children.iter().position(|child| *child == b).unwrap();
+        children.swap(index_a, index_b);
+        self.taffy.set_children(parent, &children)?;
+        self.layout_changed();
+        Ok(())
+    }
+
+    /// Replace the element of `node` while keeping its `NodeId` and children, returns the old element.
+    pub fn replace_element(&mut self, node: NodeId, element: Element) -> Option<Element> {
+        if !self.elements.contains_key(&node) {
+            return None;
+        }
         match element.get_element_type() {
-            crate::components::element::ElementType::Content => {},
+            crate::components::element::ElementType::Content | crate::components::element::ElementType::Inspector => {
+                if self.debug_views.contains_key(&node) {
+                    self.clear_debug_view(node);
+                    self.debug_views.remove(&node);
+                }
+            },
             crate::components::element::ElementType::Debug => {
-                self.debug_root = child
+                if !self.debug_views.contains_key(&node) {
+                    let root = self.root;
+                    self.add_debug_view(node, root);
+                }
             },
         }
-        self.taffy.add_child(self.root, child).unwrap();
-        self.elements.insert(child, element);
-        self.taffy.compute_layout(self.root, Size::MAX_CONTENT).expect("msg");
-        child
+        let old = self.elements.remove(&node).unwrap();
+        self.unregister(&old);
+        if let Some(shape) = old.shape.as_ref() {
+            if !element.shape.as_ref().is_some_and(|new| Arc::ptr_eq(new, shape)) {
+                self.removed_shapes.push(shape.clone());
+            }
+        }
+        self.register(node, element);
+        self.taffy.set_style(node, self.element_style(node)).expect("replace_element");
+        self.layout_changed();
+        Some(old)
+    }
+
+    /// Show the subtree under `target` in the debug tree panel `panel`.
+    pub fn add_debug_view(&mut self, panel: NodeId, target: NodeId) {
+        self.debug_views.insert(panel, DebugView {
+            target,
+            visible: true,
+            debuge_relations: HashMap::new(),
+            mirrored_children: HashMap::new(),
+            version: 0,
+        });
+    }
+
+    pub fn debug_views(&self) -> impl Iterator<Item = (&NodeId, &DebugView)> {
+        self.debug_views.iter()
+    }
+
+    /// Point `panel` at another subtree, the mirror tree is rebuilt in the next `update`.
+    pub fn set_debug_target(&mut self, panel: NodeId, target: NodeId) {
+        if self.debug_views.contains_key(&panel) {
+            self.clear_debug_view(panel);
+            self.debug_views.get_mut(&panel).unwrap().target = target;
+        }
+    }
+
+    /// Show or hide a debug view, the mirror elements are removed while it is hidden.
+    pub fn set_debug_visible(&mut self, panel: NodeId, visible: bool) {
+        let Some(view) = self.debug_views.get_mut(&panel) else {
+            return;
+        };
+        view.visible = visible;
+        if !visible {
+            self.clear_debug_view(panel);
+        }
+        self.taffy.set_style(panel, self.element_style(panel)).expect("set_debug_visible");
+        self.layout_changed();
+    }
+
+    pub fn toggle_debug_view(&mut self, panel: NodeId) {
+        if let Some(view) = self.debug_views.get(&panel) {
+            let visible = !view.visible;
+            self.set_debug_visible(panel, visible);
+        }
+    }
+
+    fn clear_debug_view(&mut self, panel: NodeId) {
+        let children: Vec<NodeId> = self.taffy.child_ids(panel).collect();
+        for child in children {
+            self.remove_element(child);
+        }
+        if let Some(view) = self.debug_views.get_mut(&panel) {
+            view.debuge_relations.clear();
+            view.mirrored_children.clear();
+        }
+    }
+
+    /// `Element::style()` of `node`, hidden debug views are not displayed.
+    fn element_style(&self, node: NodeId) -> Style {
+        let style = self.elements.get(&node).unwrap().style();
+        match self.debug_views.get(&node) {
+            Some(view) if !view.visible => Style {
+                display: Display::None,
+                ..style
+            },
+            _ => style,
+        }
+    }
+
+    pub fn handle(&self, node: NodeId) -> Option<ElementHandle> {
+        self.elements.get(&node).map(|element| ElementHandle(element.id()))
+    }
+
+    pub fn node(&self, handle: ElementHandle) -> Option<NodeId> {
+        self.handles.get(&handle).copied()
+    }
+
+    pub(crate) fn element(&self, node: NodeId) -> Option<&Element> {
+        self.elements.get(&node)
+    }
+
+    pub fn get(&self, handle: ElementHandle) -> Option<&Element> {
+        self.elements.get(self.handles.get(&handle)?)
+    }
+
+    /// Layout properties changed through the returned element are applied in the next `update`.
+    pub fn get_mut(&mut self, handle: ElementHandle) -> Option<&mut Element> {
+        let node = *self.handles.get(&handle)?;
+        self.restyles.insert(node);
+        self.dirty = true;
+        self.paint_order.take();
+        self.elements.get_mut(&node)
+    }
+
+    /// Computed taffy layout, the location is relative to the parent.
+    pub fn layout(&self, handle: ElementHandle) -> Option<&taffy::Layout> {
+        self.taffy.layout(self.node(handle)?).ok()
+    }
+
+    /// Drawn rect of the element as of the last `update`.
+    pub fn rect(&self, handle: ElementHandle) -> Option<Rect> {
+        self.get(handle).map(|element| element.get_rect())
+    }
+
+    pub fn find_by_key(&self, key: &str) -> Option<ElementHandle> {
+        self.keys.get(key).copied()
+    }
+
+    pub fn find_by_title(&self, title: &str) -> Vec<ElementHandle> {
+        self.elements
+            .values()
+            .filter(|element| element.tile == title)
+            .map(|element| ElementHandle(element.id()))
+            .collect()
+    }
+
+    fn register(&mut self, node: NodeId, mut element: Element) {
+        if self.handles.contains_key(&ElementHandle(element.id())) {
+            element.renew_id();
+        }
+        let handle = ElementHandle(element.id());
+        self.handles.insert(handle, node);
+        if let Some(key) = element.get_key() {
+            if self.keys.contains_key(key) {
+                warn!("duplicate element key {key:?}, the last element pushed with it takes it over");
+            }
+            self.keys.insert(key.to_string(), handle);
+        }
+        self.elements.insert(node, element);
+    }
+
+    fn unregister(&mut self, element: &Element) {
+        let handle = ElementHandle(element.id());
+        self.handles.remove(&handle);
+        if let Some(key) = element.get_key() {
+            if self.keys.get(key) == Some(&handle) {
+                self.keys.remove(key);
+            }
+        }
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.taffy.parent(node)
+    }
+
+    fn is_ancestor(&self, ancestor: NodeId, node: NodeId) -> bool {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.taffy.parent(id);
+        }
+        false
+    }
+
+    /// Defer layout computation until the matching `commit`. Batches can be nested.
+    pub fn begin_batch(&mut self) {
+        self.batch_depth += 1;
+    }
+
+    /// Close a batch opened by `begin_batch`, the outermost commit computes the layout once.
+    pub fn commit(&mut self) {
+        self.batch_depth = self.batch_depth.saturating_sub(1);
+        if self.batch_depth == 0 {
+            self.compute_layout();
+        }
     }
 
-    pub fn push_element_with_id(&mut self, element: Element, id: NodeId) -> NodeId {
+    /// Run `f` inside a batch, so the pushes in it only cost one layout pass.
+    pub fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.begin_batch();
+        let result = f(self);
+        self.commit();
+        result
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Recompute the taffy layout if a style, the window size or a child list changed.
+    /// Elements changed in place through `get_mut` are restyled first.
+    pub fn compute_layout(&mut self) {
+        if self.dirty {
+            self.paint_order.take();
+            let restyles: Vec<NodeId> = self.restyles.drain().collect();
+            for node in restyles {
+                self.taffy.set_style(node, self.element_style(node)).expect("restyle");
+            }
+            self.taffy.compute_layout(self.root, Size::MAX_CONTENT).expect("compute_layout");
+            if self.resolve_anchors() {
+                self.taffy.compute_layout(self.root, Size::MAX_CONTENT).expect("compute_layout");
+            }
+            self.dirty = false;
+        }
+    }
+
+    /// Turn the measured size of center anchored elements into the margin pinning their middle,
+    /// true when a margin changed and the layout needs another pass.
+    fn resolve_anchors(&mut self) -> bool {
+        let mut changed = false;
+        for (node, element) in self.elements.iter() {
+            let Some(margin) = element.anchor_margin(self.taffy.layout(*node).expect("resolve_anchors").size) else {
+                continue;
+            };
+            let style = self.taffy.style(*node).expect("resolve_anchors");
+            if style.margin != margin {
+                let style = Style { margin, ..style.clone() };
+                self.taffy.set_style(*node, style).expect("resolve_anchors");
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Push `Element::style()` of `node` to taffy again, after the element was changed in place.
+    pub fn restyle(&mut self, node: NodeId) {
+        if self.elements.contains_key(&node) {
+            self.taffy.set_style(node, self.element_style(node)).expect("restyle");
+            self.layout_changed();
+        }
+    }
+
+    fn layout_changed(&mut self) {
+        self.dirty = true;
+        self.tree_version += 1;
+        self.paint_order.take();
+        if self.batch_depth == 0 {
+            self.compute_layout();
+        }
+    }
+
+    pub fn iter(&mut self) -> impl Iterator<Item = &mut Element> {
+        self.paint_order.take();
+        self.elements.values_mut()
+    }
+
+    pub fn push_element(&mut self, element: Element) -> NodeId {
+        let root = self.root;
+        self.push_element_with_id(element, root)
+    }
+
+    pub fn push_element_with_id(&mut self, mut element: Element, id: NodeId) -> NodeId {
         let child = self
             .taffy
             .new_leaf(element.style()).unwrap();
         match element.get_element_type() {
-            crate::components::element::ElementType::Content => {},
+            crate::components::element::ElementType::Content | crate::components::element::ElementType::Inspector => {},
             crate::components::element::ElementType::Debug => {
-                self.debug_root = child
+                let root = self.root;
+                self.add_debug_view(child, root);
             },
         }
         self.taffy.add_child(id, child).unwrap();
-        self.elements.insert(child, element);
-        self.taffy.compute_layout(
-            self.root,
-            Size::MAX_CONTENT
-        ).expect("msg");
+        let virtual_rows = element.get_virtual_rows().cloned();
+        let detached = std::mem::take(&mut element.detached);
+        // a subtree returned by `remove_element` comes back in one layout pass
+        self.begin_batch();
+        self.register(child, element);
+        if let Some(rows) = virtual_rows {
+            let spacer = self.push_element_with_id(virtual_spacer(rows.count, rows.row_height), child);
+            self.virtual_lists.insert(child, VirtualList { spacer, rows: HashMap::new() });
+        }
+        for element in detached {
+            self.push_element_with_id(element, child);
+        }
+        self.layout_changed();
+        self.commit();
         child
     }
 
+    /// Push `children` under `parent`, then their own children.
+    fn push_children(&mut self, parent: NodeId, children: Option<Vec<Box<dyn UIElement>>>) {
+        for child in children.into_iter().flatten() {
+            let node = self.push_element_with_id(child.get_element(), parent);
+            self.push_children(node, child.children());
+        }
+    }
+
+    /// Change the number of rows of the virtual list `node`.
+    pub fn set_row_count(&mut self, node: NodeId, count: usize) {
+        let (Some(element), Some(list)) = (self.elements.get_mut(&node), self.virtual_lists.get(&node)) else {
+            return;
+        };
+        element.set_row_count(count);
+        let row_height = element.get_virtual_rows().unwrap().row_height;
+        let spacer = list.spacer;
+        self.replace_element(spacer, virtual_spacer(count, row_height));
+    }
+
+    /// Build the rows of the virtual lists that scrolled into view, reusing the nodes of the rows that left it.
+    fn sync_virtual_lists(&mut self) {
+        let lists: Vec<NodeId> = self.virtual_lists.keys().copied().collect();
+        for node in lists {
+            let element = &self.elements[&node];
+            let rows = element.get_virtual_rows().unwrap().clone();
+            if rows.row_height <= 0. {
+                continue;
+            }
+            let height = match element.viewport().height() > 0. {
+                true => element.viewport().height(),
+                false => self.window_size.y,
+            };
+            let offset = element.get_scroll_offset().y;
+            let first = ((offset / rows.row_height).floor() as usize).saturating_sub(VIRTUAL_OVERSCAN);
+            let last = (((offset + height) / rows.row_height).ceil() as usize + VIRTUAL_OVERSCAN).min(rows.count);
+
+            let list = self.virtual_lists.get_mut(&node).unwrap();
+            let mut free = Vec::new();
+            list.rows.retain(|index, row| {
+                let visible = (first..last).contains(index);
+                if !visible {
+                    free.push(*row);
+                }
+                visible
+            });
+            let missing: Vec<usize> = (first..last).filter(|index| !list.rows.contains_key(index)).collect();
+            for index in missing {
+                let ui = (rows.row)(index);
+                let element = ui
+                    .get_element()
+                    .position(Position::Absolute)
+                    .inset(Length::Px(0.), Length::Px(index as f32 * rows.row_height), Length::Px(0.), Length::Auto)
+                    .height(Length::Px(rows.row_height));
+                let row = match free.pop() {
+                    Some(row) => {
+                        for child in self.taffy.children(row).unwrap() {
+                            self.remove_element(child);
+                        }
+                        self.replace_element(row, element);
+                        row
+                    }
+                    None => self.push_element_with_id(element, node),
+                };
+                self.push_children(row, ui.children());
+                self.virtual_lists.get_mut(&node).unwrap().rows.insert(index, row);
+            }
+            for row in free {
+                self.remove_element(row);
+            }
+        }
+    }
+
     pub fn update_shape(&mut self, mut config: Config, mut commands: Commands) {
+        for shape in self.removed_shapes.drain(..) {
+            shape.write().unwrap().despawn(&mut commands);
+        }
+        self.update_overlay_labels(&mut config, &mut commands);
         for element in self.elements.values_mut() {
+            let visible = element.shape_visible();
             if let Some(shape) = element.shape.as_ref() {
                 let mut shape = shape.write().unwrap();
-                shape.update(&mut config,&mut commands,element.layout_anchor);
+                shape.set_visible(visible);
+                shape.set_clip(element.clip.map(|clip| clip.rect()));
+                shape.update(&mut config,&mut commands,element.shape_anchor());
                 
             }
         }
     }
     
     pub fn update(&mut self,context: &mut RwLockWriteGuard<MemState>, screen_layout_origin:Vec3) {
+        self.update_inspector(context);
+
         //setup dom tree
-        if u64::from(self.debug_root) > 0u64 && self.taffy.child_count(self.debug_root) == 0{
-            self.gen_debug_elements_tree();
-        }
+        self.batch(|layouts| {
+            layouts.sync_virtual_lists();
+            layouts.gen_debug_elements_tree();
+            layouts.sync_debug_views();
+        });
         
-        //setup win size for root
-        {
-            let old_style = self.taffy.style(self.root).expect("");
-            self.taffy.set_style(self.root, Style{
-                size:Size {
-                    width: Dimension::Length(screen_layout_origin.x * -2.),
-                    height: Dimension::Length(screen_layout_origin.y * 2.),
-                },
-                ..old_style.clone()
-            }).expect("msg");
-            //println!("root style:{:?}",self.taffy.style(self.root).expect("").size);
+        //setup win size for root and content_node
+        let window_size = Vec2::new(screen_layout_origin.x * -2., screen_layout_origin.y * 2.);
+        if window_size != self.window_size || self.dirty {
+            self.window_size = window_size;
+            let mut nodes = vec![self.root];
+            if self.taffy.child_count(self.root) > 0 {
+                nodes.push(self.taffy.get_child_id(self.root, 0));
+            }
+            for node in nodes {
+                let old_style = self.taffy.style(node).expect("");
+                self.taffy.set_style(node, Style{
+                    size:Size {
+                        width: Dimension::Length(window_size.x),
+                        height: Dimension::Length(window_size.y),
+                    },
+                    ..old_style.clone()
+                }).expect("msg");
+            }
+            self.dirty = true;
         }
+        
+        self.compute_layout();
+        self.update_focus(context);
+        self.update_group_drag(context);
+        self.traverse_update(self.root,screen_layout_origin,Vec3::new(0.,0.,0.), context,None,None);
+        self.update_depths();
+        self.update_hover();
+        self.update_drag_drop(context);
+        self.update_cursor();
 
-        //setup win size for content_node
-        {
-            let content_node = self.taffy.get_child_id(self.root, 0);
-            let old_style = self.taffy.style(content_node).expect("");
-            self.taffy.set_style(content_node, Style{
-                size:Size {
-                    width: Dimension::Length(screen_layout_origin.x * -2.),
-                    height: Dimension::Length(screen_layout_origin.y * 2.),
-                },
-                ..old_style.clone()
-            }).expect("msg");
-            //println!("root style:{:?}",self.taffy.style(content_node).expect("").size);
+        // the target can go away with a recycled virtual list row
+        if let Some(mut inspector) = self.inspector.take() {
+            match inspector.refresh(self) {
+                true => self.inspector = Some(inspector),
+                false => {
+                    self.remove_element(inspector.panel);
+                    context.inspected = -1;
+                }
+            }
         }
-        
-        self.taffy.compute_layout(self.root, taffy::Size::MAX_CONTENT).expect("");
-        self.traverse_update(self.root,screen_layout_origin,Vec3::new(0.,0.,0.), context,None);
 
-        for (element,debug_element) in self.debuge_relations.iter() {
-            let render_state = self.elements.get_mut(element).unwrap().get_render_state();
-            if render_state.is_some(){
-                self.elements.get_mut(debug_element).unwrap().set_render_state(render_state.unwrap());
+        for view in self.debug_views.values() {
+            for (element,debug_element) in view.debuge_relations.iter() {
+                let render_state = self.elements.get_mut(element).unwrap().get_render_state();
+                if render_state.is_some(){
+                    self.elements.get_mut(debug_element).unwrap().set_render_state(render_state.unwrap());
+                }
             }
         }
     }
@@ -145,24 +765,184 @@ impl UILayouts {
     pub fn draw(&mut self, painter: &mut ShapePainter) {
         self.traverse_draw(self.root,painter,Vec3::new(0.,0., 0.));
         self.draw_tree(painter);
+        let top = self.top_depth();
+        if self.bounds_overlay {
+            for (node, element) in self.elements.iter() {
+                if self.taffy.style(*node).expect("").display != Display::None {
+                    overlay::draw_bounds(painter, element, top);
+                }
+            }
+        }
+        if let Some(band) = self.rubber_band.as_ref() {
+            overlay::draw_rubber_band(painter, band.rect(), top);
+        }
+        if let Some(group) = self.group_drag.as_ref() {
+            overlay::draw_guides(painter, &group.guides, top);
+        }
+        if let Some(session) = self.drag_drop.as_ref() {
+            if let Some(source) = self.elements.get(&session.source) {
+                overlay::draw_ghost(painter, source, session.ghost, self.ghost_depth());
+            }
+        }
+    }
+
+    /// Depth above every element, where the overlays are drawn.
+    fn top_depth(&self) -> f32 {
+        self.paint_order().len() as f32 * DEPTH_STEP
+    }
+
+    /// Depth of the drag ghost, above every element and overlay.
+    fn ghost_depth(&self) -> f32 {
+        self.top_depth() + DEPTH_STEP
+    }
+
+    /// Give every visible element the depth of its place in the paint order, so shapes and
+    /// text meshes stack like the elements. Scroll views get the depth right above their
+    /// last drawn descendant for their scrollbars.
+    fn update_depths(&mut self) {
+        let order = self.paint_order().to_vec();
+        let depth = |index: usize| index as f32 * DEPTH_STEP;
+        // nodes whose subtree is still being walked, the subtree ends before the next node that isn't a descendant
+        let mut open: Vec<NodeId> = Vec::new();
+        for (index, node) in order.iter().enumerate() {
+            let parent = self.taffy.parent(*node);
+            while open.last().is_some_and(|top| Some(*top) != parent) {
+                let closed = open.pop().unwrap();
+                self.elements.get_mut(&closed).unwrap().scrollbar_depth = depth(index) - DEPTH_STEP / 2.;
+            }
+            self.elements.get_mut(node).unwrap().layout_anchor.z = depth(index);
+            open.push(*node);
+        }
+        for node in open {
+            self.elements.get_mut(&node).unwrap().scrollbar_depth = depth(order.len()) - DEPTH_STEP / 2.;
+        }
+    }
+
+    /// Outline every element with its margin and padding, labeled with its title and size.
+    pub fn set_bounds_overlay(&mut self, enable: bool) {
+        self.bounds_overlay = enable;
+    }
+
+    pub fn bounds_overlay(&self) -> bool {
+        self.bounds_overlay
+    }
+
+    fn update_overlay_labels(&mut self, config: &mut Config, commands: &mut Commands) {
+        let elements = &self.elements;
+        self.overlay_labels.retain(|node, label| {
+            let keep = elements.contains_key(node);
+            if !keep {
+                label.despawn(commands);
+            }
+            keep
+        });
+        if !self.bounds_overlay {
+            for (_, mut label) in self.overlay_labels.drain() {
+                label.despawn(commands);
+            }
+            return;
+        }
+        let top = self.top_depth();
+        for (node, element) in self.elements.iter() {
+            if self.taffy.style(*node).expect("").display == Display::None {
+                continue;
+            }
+            let label = self.overlay_labels.entry(*node).or_insert_with(|| {
+                let mut label = Text::new(String::new());
+                label.set_size(Vec2::new(0., 12.));
+                label
+            });
+            label.set_text(&overlay::bounds_label(element));
+            label.update(config, commands, overlay::label_anchor(element, top));
+        }
     }
 
     pub fn exc_action(&mut self, context: &mut RwLockWriteGuard<MemState>) {
+        let clicks: Vec<(NodeId, UIMouseState)> = self
+            .elements
+            .iter()
+            .filter(|(_, element)| matches!(element.action_state, UIMouseState::Click | UIMouseState::DoubleClick))
+            .map(|(node, element)| (*node, element.action_state))
+            .collect();
+        let mut menu_clicked = false;
+        for (target, event) in clicks {
+            let prevented = self.dispatch_click(target, event, context);
+            self.elements.get_mut(&target).unwrap().action_state = UIMouseState::Release;
+            // a click inside the context menu runs its callbacks then closes the menu
+            menu_clicked |= !prevented && self.context_menu.is_some_and(|menu| self.ancestors(target).contains(&menu));
+        }
         for element in self.elements.values_mut() {
             element.execute( context);
         }
+        if menu_clicked {
+            self.close_context_menu();
+        }
+    }
+
+    /// Run the capture callbacks of a click on `target` from the root down, then its click
+    /// callbacks back up the parent chain, until one stops the propagation.
+    /// Tells whether a callback prevented the default behavior.
+    fn dispatch_click(&mut self, target: NodeId, event: UIMouseState, cxt: &mut RwLockWriteGuard<MemState>) -> bool {
+        cxt.begin_event();
+        let path = self.ancestors(target);
+        let capture = path.iter().rev().map(|node| (*node, true));
+        let bubble = path.iter().map(|node| (*node, false));
+        for (node, capture) in capture.chain(bubble) {
+            if cxt.is_propagation_stopped() {
+                break;
+            }
+            if let Some(element) = self.elements.get_mut(&node) {
+                element.pointer_event(event, capture, cxt);
+            }
+        }
+        cxt.is_default_prevented()
+    }
+
+    /// Open, close or edit the inspector as requested through `MemState`.
+    fn update_inspector(&mut self, context: &mut RwLockWriteGuard<MemState>) {
+        if self.inspector.as_ref().is_some_and(|inspector| !self.elements.contains_key(&inspector.panel)) {
+            self.inspector = None;
+        }
+        let target = self.node(ElementHandle(context.inspected));
+        let current = self.inspector.as_ref().map(|inspector| inspector.target);
+        if target != current {
+            if let Some(inspector) = self.inspector.take() {
+                self.remove_element(inspector.panel);
+            }
+            match target {
+                Some(target) => self.inspector = Some(Inspector::open(self, target)),
+                None => context.inspected = -1,
+            }
+        }
+
+        let edits: Vec<(InspectorField, f32)> = context.inspector_edits.drain(..).collect();
+        if let Some(target) = target {
+            for (field, delta) in edits {
+                self.elements.get_mut(&target).unwrap().apply_edit(field, delta);
+                self.restyles.insert(target);
+                self.dirty = true;
+            }
+        }
+    }
+
+    fn is_debug_node(&self, node: NodeId) -> bool {
+        self.debug_views.contains_key(&node) || self.inspector.as_ref().is_some_and(|inspector| inspector.panel == node)
     }
 
     fn draw_tree(&mut self, painter: &mut ShapePainter) {
         painter.set_translation(Vec3::ZERO);
         painter.set_color(BLACK);
-        for (element_id,debuge_element_id) in self.debuge_relations.iter(){
-            let pareant_p = self.elements.get(debuge_element_id).unwrap().layout_anchor;
-            for child_element_id in  self.taffy.child_ids(*element_id){
-                let child_debug_element_id = self.debuge_relations.get(&child_element_id);
-                if child_debug_element_id.is_some(){
-                    let child_p = self.elements.get(child_debug_element_id.unwrap()).unwrap().layout_anchor;
-                    Curve::new(pareant_p + Vec3::NEG_Z,child_p + Vec3::NEG_Z).draw(painter);
+        for view in self.debug_views.values() {
+            for (element_id,debuge_element_id) in view.debuge_relations.iter(){
+                let pareant_p = self.elements.get(debuge_element_id).unwrap().layout_anchor;
+                for child_element_id in  self.taffy.child_ids(*element_id){
+                    let child_debug_element_id = view.debuge_relations.get(&child_element_id);
+                    if child_debug_element_id.is_some(){
+                        let child_p = self.elements.get(child_debug_element_id.unwrap()).unwrap().layout_anchor;
+                        // right under the mirror nodes
+                        let below = Vec3::Z * DEPTH_STEP / 2.;
+                        Curve::new(pareant_p - below,child_p - below).draw(painter);
+                    }
                 }
             }
         }
@@ -178,15 +958,19 @@ impl UILayouts {
     }
     
     //TODO: needed to optimize
-    fn traverse_update(&mut self, node: NodeId,screen_layout_origin:Vec3, inhert_origin:Vec3, cxt:&mut RwLockWriteGuard<MemState>, inherit_render_state: Option<UIMouseState>) {
+    fn traverse_update(&mut self, node: NodeId,screen_layout_origin:Vec3, inhert_origin:Vec3, cxt:&mut RwLockWriteGuard<MemState>, inherit_render_state: Option<UIMouseState>, clip: Option<Clip>) {
         let children:Vec<NodeId> =  self.taffy.child_ids(node).collect();
         for child in children.iter() {
+            if self.taffy.style(*child).expect("").display == Display::None {
+                continue;
+            }
             let layout = self.taffy.layout(*child).expect("布局错误");
             let element = self.elements.get_mut(child).unwrap();
             let mut blockstate = None;
 
             {
                 //Update state
+                element.clip = clip;
                 element.update_layout(layout, screen_layout_origin.clone(), inhert_origin,cxt);
                 element.update_render_state((cxt.mouse_position.x,cxt.mouse_position.y), screen_layout_origin.clone());
                 
@@ -209,42 +993,199 @@ impl UILayouts {
                     }
                 }
             }
-            let origin_new = Vec3::new(layout.location.x +  element.anchor_offset.x  + element.rubber_offset.x,layout.location.y -  element.anchor_offset.y - element.rubber_offset.y,0.) + inhert_origin;
-            self.traverse_update(*child, screen_layout_origin, origin_new, cxt, blockstate);
+            let scroll = element.get_scroll_offset();
+            let origin_new = Vec3::new(layout.location.x + element.anchor_offset.x  + element.rubber_offset.x - scroll.x,layout.location.y -  element.anchor_offset.y - element.rubber_offset.y - scroll.y,0.) + inhert_origin;
+            let child_clip = match (clip, element.child_clip()) {
+                (Some(clip), Some(own)) => Some(clip.intersect(own)),
+                (clip, own) => own.or(clip),
+            };
+            self.traverse_update(*child, screen_layout_origin, origin_new, cxt, blockstate, child_clip);
         }
     }
     
     //TODO: needed to optimize
     fn traverse_draw(&mut self, node: NodeId,painter: &mut ShapePainter, origin:Vec3) {
-        let children:Vec<NodeId> =  self.taffy.child_ids(node).collect();
+        let children = self.stacked_children(node);
         for child in children.iter() {
             let layout = self.taffy.layout(*child).expect("布局错误");
             let element = self.elements.get_mut(child).unwrap();
             let origin_new = Vec3::new(layout.location.x,layout.location.y,0.) + origin;
             element.draw(painter);
             self.traverse_draw(*child, painter, origin_new);
+            let element = self.elements.get(child).unwrap();
+            if element.is_scroll_view() {
+                element.draw_scrollbars(painter);
+            }
         }
     }
 
-    pub fn gen_debug_elements_tree(&mut self){
-        if u64::from(self.debug_root) > 0{
-            self.traverse_gen_debug_element(self.root, self.debug_root);
-        }
+    fn to_painter(&self, cursor: Vec2) -> Vec2 {
+        Vec2::new(cursor.x - self.window_size.x / 2., self.window_size.y / 2. - cursor.y)
     }
 
-    // to debug tree
-    fn traverse_gen_debug_element(&mut self, node: NodeId, p_node:NodeId) {
-        if u64::from(node) == u64::from(self.debug_root)
-        {
-            return;
+    /// Innermost visible scroll view under `cursor` (window coordinates) that can still move along `delta`.
+    fn scroll_view_at(&self, cursor: Vec2, delta: Vec2) -> Option<NodeId> {
+        let point = self.to_painter(cursor);
+        let mut nodes = vec![(self.root, 0)];
+        let mut found: Option<(NodeId, usize)> = None;
+        while let Some((node, depth)) = nodes.pop() {
+            for child in self.taffy.child_ids(node) {
+                if self.taffy.style(child).expect("").display == Display::None {
+                    continue;
+                }
+                nodes.push((child, depth + 1));
+            }
+            let element = &self.elements[&node];
+            if !element.is_scroll_view() || !element.viewport().contains(point) {
+                continue;
+            }
+            if element.clip.is_some_and(|clip| !clip.contains(point)) {
+                continue;
+            }
+            let target = (element.get_scroll_offset() + delta).clamp(Vec2::ZERO, element.max_scroll());
+            if target != element.get_scroll_offset() && found.map_or(true, |(_, found)| depth > found) {
+                found = Some((node, depth));
+            }
         }
-        let children:Vec<NodeId> =  self.taffy.child_ids(node).collect();
-        let mut v_node = p_node;
+        found.map(|(node, _)| node)
+    }
 
-        let mut self_element = rectangle()
-            .round(5.).size(Vec2::new(100., 50.))
-            .margin(Vec4::new(10.,10.,10.,10.))
-            .color(BLACK);
+    /// Scroll the scroll view under `cursor` by `delta` pixels, returns false when nothing moved.
+    pub fn scroll(&mut self, cursor: Vec2, delta: Vec2) -> bool {
+        match self.scroll_view_at(cursor, delta) {
+            Some(node) => self.elements.get_mut(&node).unwrap().scroll_by(delta),
+            None => false,
+        }
+    }
+
+    /// Start dragging the scrollbar thumb under `cursor`, returns false when there is none.
+    /// Only the scroll views around the topmost element under the cursor can take the press,
+    /// the outer ones first since their scrollbars are drawn above the inner ones.
+    pub fn begin_scroll_drag(&mut self, cursor: Vec2) -> bool {
+        let point = self.to_painter(cursor);
+        for node in self.hit_path(cursor).into_iter().rev() {
+            let element = &self.elements[&node];
+            if !element.is_scroll_view() || element.clip.is_some_and(|clip| !clip.contains(point)) {
+                continue;
+            }
+            for vertical in [true, false] {
+                if element.scrollbar(vertical).is_some_and(|(track, _)| track.contains(point)) {
+                    self.scroll_drag = Some((node, vertical, cursor));
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn drag_scrollbar(&mut self, cursor: Vec2) {
+        let Some((node, vertical, last)) = self.scroll_drag else {
+            return;
+        };
+        let Some(element) = self.elements.get_mut(&node) else {
+            self.scroll_drag = None;
+            return;
+        };
+        let delta = match vertical {
+            true => Vec2::new(0., element.thumb_to_scroll(true, cursor.y - last.y)),
+            false => Vec2::new(element.thumb_to_scroll(false, cursor.x - last.x), 0.),
+        };
+        element.scroll_by(delta);
+        self.scroll_drag = Some((node, vertical, cursor));
+    }
+
+    pub fn end_scroll_drag(&mut self) {
+        self.scroll_drag = None;
+    }
+
+    pub fn is_dragging_scrollbar(&self) -> bool {
+        self.scroll_drag.is_some()
+    }
+
+    /// Build the mirror tree of every visible debug view that is still empty.
+    pub fn gen_debug_elements_tree(&mut self){
+        let panels: Vec<(NodeId, NodeId)> = self.debug_views
+            .iter()
+            .filter(|(panel, view)| view.visible && self.taffy.child_count(**panel) == 0)
+            .map(|(panel, view)| (*panel, view.target))
+            .collect();
+        for (panel, target) in panels {
+            self.traverse_gen_debug_element(target, panel, panel);
+        }
+    }
+
+    /// Regenerate the mirrors of the nodes whose children changed since the last sync.
+    pub fn sync_debug_views(&mut self) {
+        let tree_version = self.tree_version;
+        let panels: Vec<NodeId> = self.debug_views
+            .iter()
+            .filter(|(panel, view)| view.visible && view.version != tree_version && self.taffy.child_count(**panel) > 0)
+            .map(|(panel, _)| *panel)
+            .collect();
+        if panels.is_empty() {
+            return;
+        }
+        for panel in panels {
+            let view = self.debug_views.get(&panel).unwrap();
+            let mut nodes = vec![view.target];
+            let mut stale = Vec::new();
+            while let Some(node) = nodes.pop() {
+                let children = self.debug_children(node);
+                if view.mirrored_children.get(&node) != Some(&children) {
+                    stale.push(node);
+                    continue;
+                }
+                nodes.extend(children);
+            }
+            for node in stale {
+                self.regen_debug_element(panel, node);
+            }
+        }
+        for view in self.debug_views.values_mut() {
+            view.version = self.tree_version;
+        }
+    }
+
+    fn regen_debug_element(&mut self, panel: NodeId, node: NodeId) {
+        let view = self.debug_views.get(&panel).unwrap();
+        let (Some(self_node), Some(children)) = (view.debuge_relations.get(&node), view.mirrored_children.get(&node)) else {
+            return;
+        };
+        let container = match children.is_empty() {
+            true => *self_node,
+            false => self.taffy.parent(*self_node).unwrap(),
+        };
+        let parent = self.taffy.parent(container).unwrap();
+        let index = self.taffy.child_ids(parent).position(|child| child == container).unwrap();
+        self.remove_element(container);
+        self.traverse_gen_debug_element(node, parent, panel);
+        let new_container = self.taffy.get_child_id(parent, self.taffy.child_count(parent) - 1);
+        self.move_element(new_container, parent, index).expect("regen_debug_element");
+    }
+
+    /// Children of `node` shown in the debug tree, debug panels and the inspector are left out.
+    fn debug_children(&self, node: NodeId) -> Vec<NodeId> {
+        self.taffy
+            .child_ids(node)
+            .filter(|child| !self.is_debug_node(*child))
+            .collect()
+    }
+
+    // to debug tree
+    fn traverse_gen_debug_element(&mut self, node: NodeId, p_node:NodeId, panel: NodeId) {
+        if self.is_debug_node(node)
+        {
+            return;
+        }
+        let children:Vec<NodeId> =  self.debug_children(node);
+        let mut v_node = p_node;
+
+        let id = self.elements.get(&node).unwrap().id();
+        let mut self_element = rectangle()
+            .round(5.).size(Vec2::new(100., 50.))
+            .margin(Vec4::new(10.,10.,10.,10.))
+            .color(BLACK)
+            .click(move |_, cxt| cxt.inspected = id);
 
         if children.len() >  0 {
             let v_stack = 
@@ -257,7 +1198,9 @@ impl UILayouts {
         }
 
         let self_node = self.push_element_with_id(self_element, v_node);
-        self.debuge_relations.insert(node,self_node);
+        let view = self.debug_views.get_mut(&panel).unwrap();
+        view.debuge_relations.insert(node,self_node);
+        view.mirrored_children.insert(node, children.clone());
 
         let tile = self.elements.get(&node).unwrap().tile.as_str();
 
@@ -275,7 +1218,7 @@ impl UILayouts {
         let h2: NodeId = self.push_element_with_id(h2_stack, v_node);
 
         for child in children.iter() {
-            self.traverse_gen_debug_element(*child,h2);
+            self.traverse_gen_debug_element(*child,h2,panel);
         }
     }
 
@@ -283,13 +1226,2131 @@ impl UILayouts {
         self.taffy.print_tree(self.root);
     }
 
+    /// Visible nodes in tree order, parents before their children.
+    pub(crate) fn visible_nodes(&self) -> Vec<NodeId> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(node) = stack.pop() {
+            nodes.push(node);
+            let children: Vec<NodeId> = self.taffy.child_ids(node).collect();
+            for child in children.into_iter().rev() {
+                if self.taffy.style(child).expect("").display != Display::None {
+                    stack.push(child);
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Point of a window `cursor` in the space of `Element::insection`.
+    fn to_hit_point(&self, cursor: Vec2) -> Vec2 {
+        Vec2::new(cursor.x - self.window_size.x / 2., cursor.y - self.window_size.y / 2.)
+    }
+
+    /// Focusable elements in Tab order, positive tab indices first then the tree order.
+    pub fn focus_order(&self) -> Vec<NodeId> {
+        let mut order: Vec<NodeId> = self
+            .visible_nodes()
+            .into_iter()
+            .filter(|node| self.elements[node].is_focusable() && self.elements[node].get_tab_index() >= 0)
+            .collect();
+        order.sort_by_key(|node| match self.elements[node].get_tab_index() {
+            0 => i32::MAX,
+            tab_index => tab_index,
+        });
+        order
+    }
+
+    pub fn focused(&self, cxt: &RwLockWriteGuard<MemState>) -> Option<NodeId> {
+        self.node(ElementHandle(cxt.focused))
+    }
+
+    /// Give the keyboard focus to `node`, or clear it with `None`.
+    pub fn focus(&mut self, cxt: &mut RwLockWriteGuard<MemState>, node: Option<NodeId>) {
+        cxt.focused = node.and_then(|node| self.handle(node)).map_or(-1, |handle| handle.id());
+        self.update_focus(cxt);
+    }
+
+    /// Move the focus to the next element of the Tab order, or the previous one with `reverse`.
+    pub fn focus_next(&mut self, cxt: &mut RwLockWriteGuard<MemState>, reverse: bool) {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return;
+        }
+        let current = self.focused(cxt).and_then(|node| order.iter().position(|other| *other == node));
+        let next = match (current, reverse) {
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+            (Some(index), false) => (index + 1) % order.len(),
+            (Some(index), true) => (index + order.len() - 1) % order.len(),
+        };
+        self.focus(cxt, Some(order[next]));
+    }
+
+    /// Focus the innermost focusable element under `cursor`, clicking elsewhere clears the focus.
+    pub fn focus_at(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2) {
+        let node = self.element_at(cursor, |element| element.is_focusable());
+        self.focus(cxt, node);
+    }
+
+    /// Run the click callback of the focused element, for Enter and Space.
+    pub fn activate_focused(&mut self, cxt: &mut RwLockWriteGuard<MemState>) {
+        if let Some(node) = self.focused(cxt) {
+            self.elements.get_mut(&node).unwrap().action_state = UIMouseState::Click;
+        }
+    }
+
+    /// Drop the focus of removed or hidden elements and mark the focused one for its focus ring.
+    fn update_focus(&mut self, cxt: &mut RwLockWriteGuard<MemState>) {
+        let focused = self.focused(cxt).filter(|node| self.paint_order().contains(node));
+        if focused.is_none() {
+            cxt.focused = -1;
+        }
+        for (node, element) in self.elements.iter_mut() {
+            element.focused = Some(*node) == focused;
+        }
+    }
+
+    /// Visible children of `node` from the bottom to the top, by z-order then tree order.
+    fn stacked_children(&self, node: NodeId) -> Vec<NodeId> {
+        let mut children: Vec<NodeId> = self
+            .taffy
+            .child_ids(node)
+            .filter(|child| self.taffy.style(*child).expect("").display != Display::None)
+            .collect();
+        children.sort_by_key(|child| self.elements[child].get_z_order());
+        children
+    }
+
+    /// Visible nodes in the order they are drawn, each subtree stacked right above its root.
+    /// Built once and kept until the tree, a style or a z-order changes.
+    pub fn paint_order(&self) -> &[NodeId] {
+        self.paint_order.get_or_init(|| {
+            let mut nodes = Vec::new();
+            let mut stack = vec![self.root];
+            while let Some(node) = stack.pop() {
+                nodes.push(node);
+                stack.extend(self.stacked_children(node).into_iter().rev());
+            }
+            nodes
+        })
+    }
+
+    /// Topmost visible element matching `filter`, the last one drawn.
+    fn topmost(&self, filter: impl Fn(&Element) -> bool) -> Option<NodeId> {
+        self.paint_order().iter().rev().find(|node| filter(&self.elements[node])).copied()
+    }
+
+    /// Only the topmost element under the cursor and its ancestors stay hovered,
+    /// with the descendants of hovered groups.
+    fn update_hover(&mut self) {
+        let hovered = |element: &Element| element.render_state == UIMouseState::Hover;
+        let Some(hit) = self.topmost(hovered) else {
+            return;
+        };
+        let chain = self.ancestors(hit);
+        let mut grouped = HashSet::new();
+        let mut nodes: Vec<NodeId> = chain.iter().copied().filter(|node| self.elements[node].is_group()).collect();
+        while let Some(node) = nodes.pop() {
+            nodes.extend(self.taffy.child_ids(node).filter(|child| grouped.insert(*child)));
+        }
+        let covered: Vec<NodeId> = self
+            .elements
+            .iter()
+            .filter(|(node, element)| hovered(element) && !chain.contains(node) && !grouped.contains(*node))
+            .map(|(node, _)| *node)
+            .collect();
+        for node in covered {
+            let element = self.elements.get_mut(&node).unwrap();
+            element.render_state = element.idle_state();
+        }
+    }
+
+    /// Cursor of the pressed element, else of the topmost hovered element asking for one.
+    fn update_cursor(&mut self) {
+        let pressed = |element: &Element| matches!(element.action_state, UIMouseState::Pressed | UIMouseState::Drag);
+        let node = self
+            .topmost(|element| pressed(element) && element.get_cursor().is_some())
+            .or_else(|| self.topmost(|element| element.hover_cursor().is_some()));
+        self.cursor = node.and_then(|node| self.elements[&node].hover_cursor()).unwrap_or_default();
+    }
+
+    /// Cursor to show in the window, applied to the primary window each frame.
+    pub fn cursor(&self) -> CursorIcon {
+        self.cursor
+    }
+
+    /// Start a drag and drop when a drag source starts dragging, then send the enter, over
+    /// and leave events to the drop target under the cursor and the drop once released.
+    fn update_drag_drop(&mut self, cxt: &mut RwLockWriteGuard<MemState>) {
+        let cursor = cxt.mouse_position;
+        if self.drag_drop.is_none() {
+            let source = self
+                .elements
+                .iter()
+                .find(|(_, element)| element.action_state == UIMouseState::Drag && element.get_drag_payload().is_some());
+            if let Some((node, element)) = source {
+                let payload = element.get_drag_payload().unwrap().clone();
+                cxt.drag_payload = Some(payload.clone());
+                self.drag_drop = Some(DragSession { source: *node, payload, target: None, ghost: Vec2::ZERO });
+            }
+        }
+        let Some(mut session) = self.drag_drop.take() else {
+            return;
+        };
+        let dragging = self.elements.get(&session.source).is_some_and(|element| element.action_state == UIMouseState::Drag);
+        session.ghost = self.to_painter(cursor);
+
+        let target = self
+            .element_at(cursor, |element| element.is_drop_target())
+            .filter(|node| !self.ancestors(*node).contains(&session.source));
+        if target != session.target.map(|(node, _)| node) {
+            if let Some((old, accepted)) = session.target.take() {
+                if let Some(element) = self.elements.get_mut(&old) {
+                    element.drop_state = None;
+                    if accepted {
+                        element.drop_event(DropEvent::Leave, &session.payload, cxt);
+                    }
+                }
+            }
+            if let Some(node) = target {
+                let element = self.elements.get_mut(&node).unwrap();
+                let accepted = element.accepts_payload(&session.payload);
+                element.drop_state = Some(accepted);
+                if accepted {
+                    element.drop_event(DropEvent::Enter, &session.payload, cxt);
+                }
+                session.target = Some((node, accepted));
+            }
+        }
+
+        if let Some((node, accepted)) = session.target {
+            if let Some(element) = self.elements.get_mut(&node) {
+                if accepted {
+                    let event = if dragging { DropEvent::Over } else { DropEvent::Drop };
+                    element.drop_event(event, &session.payload, cxt);
+                }
+                if !dragging {
+                    element.drop_state = None;
+                }
+            }
+        }
+        if dragging {
+            self.drag_drop = Some(session);
+        } else {
+            cxt.drag_payload = None;
+        }
+    }
+
+    /// Element receiving the payload if the drag and drop in progress ended now.
+    pub fn drop_target(&self) -> Option<NodeId> {
+        self.drag_drop.as_ref()?.target.filter(|(_, accepted)| *accepted).map(|(node, _)| node)
+    }
+
+    /// Selected elements, the last one is the latest selected.
+    pub fn selection(&self, cxt: &RwLockWriteGuard<MemState>) -> Vec<NodeId> {
+        cxt.selection_group.iter().filter_map(|id| self.node(ElementHandle(*id))).collect()
+    }
+
+    /// Replace the selection, calling the selection callbacks of the elements that changed.
+    pub fn set_selection(&mut self, cxt: &mut RwLockWriteGuard<MemState>, nodes: Vec<NodeId>) {
+        let nodes: Vec<NodeId> = nodes.into_iter().filter(|node| self.elements.contains_key(node)).collect();
+        for node in self.selection(cxt) {
+            if !nodes.contains(&node) {
+                self.elements.get_mut(&node).unwrap().set_selected(false, cxt);
+            }
+        }
+        for node in nodes.iter() {
+            self.elements.get_mut(node).unwrap().set_selected(true, cxt);
+        }
+        cxt.selection_group = nodes.iter().map(|node| self.elements[node].id()).collect();
+        cxt.selection_current = cxt.selection_group.last().copied().unwrap_or(-1);
+    }
+
+    /// Change the selection with a click on `node`.
+    pub fn select(&mut self, cxt: &mut RwLockWriteGuard<MemState>, node: NodeId, mode: SelectMode) {
+        let selection = self.selection(cxt);
+        // clicking a selected element keeps the selection, to drag it as a group
+        if mode == SelectMode::Replace && selection.contains(&node) {
+            return;
+        }
+        self.set_selection(cxt, mode.apply(&selection, &[node]));
+    }
+
+    /// A press on a selectable element or its content selects it, a press on an empty spot
+    /// starts a rubber band. Tells whether the press went to the selection.
+    pub fn press_selection(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2, mode: SelectMode) -> bool {
+        let point = self.to_hit_point(cursor);
+        let Some(hit) = self.topmost(|element| element.insection(point)) else {
+            return false;
+        };
+        let chain = self.ancestors(hit);
+        if let Some(node) = chain.iter().find(|node| self.elements[node].is_selectable()) {
+            self.select(cxt, *node, mode);
+            return true;
+        }
+        let selectables = self.elements.values().any(|element| element.is_selectable());
+        if !selectables || chain.iter().any(|node| self.elements[node].is_interactive()) {
+            return false;
+        }
+        let start = self.to_painter(cursor);
+        let base = match mode {
+            SelectMode::Replace => Vec::new(),
+            _ => self.selection(cxt),
+        };
+        self.rubber_band = Some(RubberBand { start, end: start, mode, base });
+        self.set_selection(cxt, self.rubber_band.as_ref().unwrap().base.clone());
+        true
+    }
+
+    /// Select the elements intersecting the rubber band stretched to `cursor`.
+    pub fn drag_selection(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2) {
+        let end = self.to_painter(cursor);
+        let Some(band) = self.rubber_band.as_mut() else {
+            return;
+        };
+        band.end = end;
+        let rect = band.rect();
+        let hits: Vec<NodeId> = self
+            .visible_nodes()
+            .into_iter()
+            .filter(|node| {
+                let element = &self.elements[node];
+                element.is_selectable() && !element.get_rect().intersect(rect).is_empty()
+            })
+            .collect();
+        let band = self.rubber_band.as_ref().unwrap();
+        let selection = band.mode.apply(&band.base, &hits);
+        self.set_selection(cxt, selection);
+    }
+
+    pub fn end_selection(&mut self) {
+        self.rubber_band = None;
+    }
+
+    pub fn is_rubber_banding(&self) -> bool {
+        self.rubber_band.is_some()
+    }
+
+    /// Round the position of dragged selections to a grid of `size`, `None` moves them freely.
+    pub fn set_snap_grid(&mut self, size: Option<f32>) {
+        self.snap_grid = size.filter(|size| *size > 0.);
+    }
+
+    pub fn snap_grid(&self) -> Option<f32> {
+        self.snap_grid
+    }
+
+    /// Align dragged selections with the edges and centers of the leader's siblings.
+    pub fn set_alignment_guides(&mut self, enable: bool) {
+        self.alignment_guides = enable;
+    }
+
+    pub fn alignment_guides(&self) -> bool {
+        self.alignment_guides
+    }
+
+    /// Dragging a selected `drag_enable` element moves the whole selection by the same offset.
+    /// Runs before the positions are updated so the members move on the same frame.
+    fn update_group_drag(&mut self, cxt: &mut RwLockWriteGuard<MemState>) {
+        if self.group_drag.is_none() && cxt.is_dragging() {
+            let leader = self.elements.iter().find(|(_, element)| {
+                matches!(element.action_state, UIMouseState::Pressed | UIMouseState::Drag) && element.is_drag_enabled() && element.is_selected()
+            });
+            if let Some((leader, element)) = leader {
+                let leader = *leader;
+                let rubber = element.rubber_offset.truncate();
+                let rect = element.get_rect();
+                let origin = Rect::from_corners(rect.min - rubber, rect.max - rubber);
+                let members: Vec<(NodeId, Vec3)> = self
+                    .selection(cxt)
+                    .into_iter()
+                    .filter(|node| self.elements[node].is_drag_enabled())
+                    .map(|node| (node, self.elements[&node].anchor_offset))
+                    .collect();
+                for (node, _) in members.iter() {
+                    let element = self.elements.get_mut(node).unwrap();
+                    element.rubber_offset = Vec3::ZERO;
+                    element.group_drag = true;
+                }
+                self.group_drag = Some(GroupDrag { leader, members, origin, guides: Vec::new() });
+            }
+        }
+        let Some(mut group) = self.group_drag.take() else {
+            return;
+        };
+        let dragging = self
+            .elements
+            .get(&group.leader)
+            .is_some_and(|element| matches!(element.action_state, UIMouseState::Pressed | UIMouseState::Drag));
+        if !dragging {
+            self.end_group_drag(group, false);
+            return;
+        }
+
+        let delta = cxt.drag_delta.1 - cxt.drag_delta.0;
+        let offset = self.group_offset(&mut group, Vec2::new(delta.x, -delta.y));
+        for (node, anchor_offset) in group.members.iter() {
+            if let Some(element) = self.elements.get_mut(node) {
+                element.anchor_offset = *anchor_offset + offset.extend(0.);
+            }
+        }
+        self.group_drag = Some(group);
+    }
+
+    /// Offset of the group for a cursor offset, snapped to the grid then to the alignment guides.
+    fn group_offset(&self, group: &mut GroupDrag, mut offset: Vec2) -> Vec2 {
+        // grid lines start at the top left corner of the window
+        if let Some(size) = self.snap_grid {
+            let corner = Vec2::new(group.origin.min.x + self.window_size.x / 2., self.window_size.y / 2. - group.origin.max.y);
+            let moved = corner + Vec2::new(offset.x, -offset.y);
+            let snapped = (moved / size).round() * size;
+            offset += Vec2::new(snapped.x - moved.x, moved.y - snapped.y);
+        }
+
+        group.guides.clear();
+        if !self.alignment_guides {
+            return offset;
+        }
+        let Some(parent) = self.taffy.parent(group.leader) else {
+            return offset;
+        };
+        let members: Vec<NodeId> = group.members.iter().map(|(node, _)| *node).collect();
+        let rect = |rect: Rect, offset: Vec2| Rect::from_corners(rect.min + offset, rect.max + offset);
+        let values = |min: f32, max: f32| [min, (min + max) / 2., max];
+        let neighbors: Vec<Rect> = self
+            .stacked_children(parent)
+            .into_iter()
+            .filter(|node| !members.contains(node))
+            .map(|node| self.elements[&node].get_rect())
+            .collect();
+
+        let moved = rect(group.origin, offset);
+        let best = |axis: usize| {
+            neighbors
+                .iter()
+                .filter_map(|other| {
+                    let (a, b) = match axis {
+                        0 => (values(moved.min.x, moved.max.x), values(other.min.x, other.max.x)),
+                        _ => (values(moved.min.y, moved.max.y), values(other.min.y, other.max.y)),
+                    };
+                    align(a, b).map(|(shift, at)| (shift, at, *other))
+                })
+                .min_by(|a, b| a.0.abs().total_cmp(&b.0.abs()))
+        };
+        let (x, y) = (best(0), best(1));
+        offset += Vec2::new(x.map_or(0., |(shift, _, _)| shift), y.map_or(0., |(shift, _, _)| shift));
+
+        let moved = rect(group.origin, offset);
+        if let Some((_, at, other)) = x {
+            let span = moved.union(other);
+            group.guides.push((Vec2::new(at, span.min.y), Vec2::new(at, span.max.y)));
+        }
+        if let Some((_, at, other)) = y {
+            let span = moved.union(other);
+            group.guides.push((Vec2::new(span.min.x, at), Vec2::new(span.max.x, at)));
+        }
+        offset
+    }
+
+    /// Stop the group drag, putting the members back where they started when `cancel`.
+    fn end_group_drag(&mut self, group: GroupDrag, cancel: bool) {
+        for (node, anchor_offset) in group.members {
+            if let Some(element) = self.elements.get_mut(&node) {
+                element.group_drag = false;
+                element.rubber_offset = Vec3::ZERO;
+                if cancel {
+                    element.anchor_offset = anchor_offset;
+                }
+            }
+        }
+        if cancel {
+            if let Some(leader) = self.elements.get_mut(&group.leader) {
+                leader.action_state = UIMouseState::Release;
+            }
+        }
+    }
+
+    /// Cancel the group drag in progress, for Escape.
+    pub fn cancel_group_drag(&mut self) {
+        if let Some(group) = self.group_drag.take() {
+            self.end_group_drag(group, true);
+        }
+    }
+
+    pub fn is_group_dragging(&self) -> bool {
+        self.group_drag.is_some()
+    }
+
+    /// Raise `node` above all of its siblings.
+    pub fn bring_to_front(&mut self, node: NodeId) {
+        let Some(parent) = self.taffy.parent(node) else {
+            return;
+        };
+        let top = self
+            .taffy
+            .child_ids(parent)
+            .filter(|child| *child != node)
+            .map(|child| self.elements[&child].get_z_order())
+            .max();
+        if let Some(top) = top {
+            if self.elements[&node].get_z_order() <= top {
+                self.elements.get_mut(&node).unwrap().set_z_order(top + 1);
+                self.paint_order.take();
+            }
+        }
+    }
+
+    /// Lower `node` below all of its siblings.
+    pub fn send_to_back(&mut self, node: NodeId) {
+        let Some(parent) = self.taffy.parent(node) else {
+            return;
+        };
+        let bottom = self
+            .taffy
+            .child_ids(parent)
+            .filter(|child| *child != node)
+            .map(|child| self.elements[&child].get_z_order())
+            .min();
+        if let Some(bottom) = bottom {
+            if self.elements[&node].get_z_order() >= bottom {
+                self.elements.get_mut(&node).unwrap().set_z_order(bottom - 1);
+                self.paint_order.take();
+            }
+        }
+    }
+
+    /// Topmost visible element under `cursor` (window coordinates) matching `filter`.
+    fn element_at(&self, cursor: Vec2, filter: impl Fn(&Element) -> bool) -> Option<NodeId> {
+        let point = self.to_hit_point(cursor);
+        self.topmost(|element| filter(element) && element.insection(point))
+    }
+
+    /// Any press outside the open context menu closes it, a right or middle press
+    /// is then tracked for the element under the cursor that handles `button`.
+    /// Tells whether an element took the press.
+    pub fn press_button(&mut self, button: MouseButton, cursor: Vec2) -> bool {
+        if let Some(menu) = self.context_menu {
+            if !self.elements.get(&menu).is_some_and(|menu| menu.insection(self.to_hit_point(cursor))) {
+                self.close_context_menu();
+            }
+        }
+        if let Some(node) = self.element_at(cursor, |element| element.handles_button(button)) {
+            self.buttons.insert(button, ButtonPress { node, start: cursor, last: cursor, dragged: false });
+            return true;
+        }
+        false
+    }
+
+    /// Hover state for `cursor` right away, for pointers that jump like touches.
+    fn hover_at(&mut self, cursor: Vec2) {
+        let origin = Vec3::new(-self.window_size.x / 2., self.window_size.y / 2., 0.);
+        for node in self.visible_nodes().into_iter().skip(1) {
+            self.elements.get_mut(&node).unwrap().update_render_state((cursor.x, cursor.y), origin);
+        }
+        self.update_hover();
+    }
+
+    /// Press of the primary pointer, the left button or the first finger, at `cursor`.
+    /// Alt + press rotates, otherwise the press goes to a scrollbar or to the elements.
+    pub fn pointer_pressed(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2, keys: &ButtonInput<KeyCode>) {
+        cxt.mouse_position = cursor;
+        self.hover_at(cursor);
+        self.press_button(MouseButton::Left, cursor);
+        if keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+            self.begin_gesture_drag(GestureDrag::Rotate, cursor);
+        } else if !self.begin_scroll_drag(cursor) {
+            self.focus_at(cxt, cursor);
+            self.press_selection(cxt, cursor, SelectMode::from_keys(keys));
+            self.update_input_state(UIMouseState::Pressed);
+        }
+        cxt.drag_delta.0 = cursor;
+        cxt.drag_delta.1 = cursor;
+        cxt.mouse_state = ButtonState::Pressed;
+    }
+
+    pub fn pointer_moved(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2) {
+        cxt.mouse_position = cursor;
+        if cxt.mouse_state == ButtonState::Pressed {
+            cxt.drag_delta.1 = cursor;
+            self.drag_scrollbar(cursor);
+            self.drag_selection(cxt, cursor);
+        }
+    }
+
+    /// Release of the primary pointer, a click or a double click when it didn't drag.
+    pub fn pointer_released(&mut self, cxt: &mut RwLockWriteGuard<MemState>) {
+        self.end_scroll_drag();
+        self.end_selection();
+        self.end_gesture_drag();
+        let state = match !cxt.is_dragging() && cxt.register_click() {
+            true => UIMouseState::DoubleClick,
+            false => UIMouseState::Release,
+        };
+        self.update_input_state(state);
+        cxt.drag_delta.2 = cxt.drag_delta.1 - cxt.drag_delta.0;
+        cxt.drag_delta.0 = cxt.mouse_position;
+        cxt.drag_delta.1 = cxt.mouse_position;
+        cxt.mouse_state = ButtonState::Released;
+    }
+
+    /// Drop the press of the primary pointer without a click, when a second finger
+    /// turns the touch into a gesture or the system cancels it.
+    pub fn pointer_canceled(&mut self, cxt: &mut RwLockWriteGuard<MemState>) {
+        self.end_scroll_drag();
+        self.end_selection();
+        self.end_gesture_drag();
+        self.cancel_group_drag();
+        for element in self.elements.values_mut() {
+            if matches!(element.action_state, UIMouseState::Pressed | UIMouseState::Drag) {
+                element.action_state = UIMouseState::Release;
+            }
+        }
+        cxt.drag_delta.2 = Vec2::ZERO;
+        cxt.drag_delta.0 = cxt.mouse_position;
+        cxt.drag_delta.1 = cxt.mouse_position;
+        cxt.mouse_state = ButtonState::Released;
+    }
+
+    /// Feed a touch event, from Bevy or synthetic. One finger acts like the left button,
+    /// two fingers pan, pinch and rotate what is under their center.
+    pub fn touch_input(&mut self, cxt: &mut RwLockWriteGuard<MemState>, event: &TouchInput, keys: &ButtonInput<KeyCode>) {
+        let (id, position) = (event.id, event.position);
+        match event.phase {
+            TouchPhase::Started => {
+                self.touches.points.push((id, position));
+                match self.touches.points.len() {
+                    1 => {
+                        self.touches.primary = Some(id);
+                        self.pointer_pressed(cxt, position, keys);
+                    }
+                    2 => {
+                        if self.touches.primary.take().is_some() {
+                            self.pointer_canceled(cxt);
+                        }
+                        self.touches.pair = self.touches.pair_shape();
+                    }
+                    _ => {}
+                }
+            }
+            TouchPhase::Moved => {
+                if let Some(point) = self.touches.points.iter_mut().find(|(touch, _)| *touch == id) {
+                    point.1 = position;
+                }
+                if self.touches.primary == Some(id) {
+                    self.pointer_moved(cxt, position);
+                    return;
+                }
+                let (Some(old), Some(new)) = (self.touches.pair, self.touches.pair_shape()) else {
+                    return;
+                };
+                self.touches.pair = Some(new);
+                if new.center != old.center {
+                    self.gesture(cxt, new.center, Gesture::Pan(new.center - old.center));
+                }
+                if old.distance > 0. && new.distance != old.distance {
+                    self.gesture(cxt, new.center, Gesture::Pinch(new.distance / old.distance - 1.));
+                }
+                let rotation = old.rotation_to(&new);
+                if rotation != 0. {
+                    self.gesture(cxt, new.center, Gesture::Rotate(rotation));
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Canceled => {
+                self.touches.points.retain(|(touch, _)| *touch != id);
+                if self.touches.primary == Some(id) {
+                    self.touches.primary = None;
+                    match event.phase {
+                        TouchPhase::Ended => self.pointer_released(cxt),
+                        _ => self.pointer_canceled(cxt),
+                    }
+                }
+                if self.touches.points.len() < 2 {
+                    self.touches.pair = None;
+                }
+            }
+        }
+    }
+
+    /// Send `gesture` to the topmost element under `cursor` then up its parent chain,
+    /// until a callback stops the propagation. Tells whether an element handled it.
+    pub fn gesture(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2, gesture: Gesture) -> bool {
+        let path = self.hit_path(cursor);
+        self.send_gesture(cxt, &path, gesture)
+    }
+
+    /// Route a wheel event. Ctrl + wheel pinches, falling back to scrolling. A trackpad pan
+    /// goes to the gesture handlers inside the scroll view under the cursor, then scrolls it,
+    /// and reaches the handlers around it only when nothing scrolled. Line scrolling scrolls.
+    pub fn wheel(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2, event: &MouseWheel, ctrl: bool) {
+        // the scroll offset moves against the content
+        let delta = -wheel_movement(event);
+        match wheel_gesture(event, ctrl) {
+            Some(pan @ Gesture::Pan(_)) => {
+                let path = self.hit_path(cursor);
+                let view = self.scroll_view_at(cursor, delta);
+                let split = view.and_then(|view| path.iter().position(|node| *node == view)).unwrap_or(path.len());
+                let (inner, outer) = path.split_at(split);
+                if self.send_gesture(cxt, inner, pan) || self.scroll(cursor, delta) {
+                    return;
+                }
+                self.send_gesture(cxt, outer, pan);
+            }
+            Some(gesture) => {
+                if !self.gesture(cxt, cursor, gesture) {
+                    self.scroll(cursor, delta);
+                }
+            }
+            None => {
+                self.scroll(cursor, delta);
+            }
+        }
+    }
+
+    /// Topmost element under `cursor` followed by its ancestors.
+    fn hit_path(&self, cursor: Vec2) -> Vec<NodeId> {
+        let point = self.to_hit_point(cursor);
+        self.topmost(|element| element.insection(point)).map_or(Vec::new(), |hit| self.ancestors(hit))
+    }
+
+    /// Send `gesture` along `path` until a callback stops the propagation.
+    fn send_gesture(&mut self, cxt: &mut RwLockWriteGuard<MemState>, path: &[NodeId], gesture: Gesture) -> bool {
+        cxt.begin_event();
+        let mut handled = false;
+        for node in path.iter() {
+            let element = self.elements.get_mut(node).unwrap();
+            if !element.handles_gesture() {
+                continue;
+            }
+            element.gesture(gesture, cxt);
+            handled = true;
+            if cxt.is_propagation_stopped() {
+                break;
+            }
+        }
+        handled
+    }
+
+    /// Start synthesizing `kind` gestures from the mouse movement, sent where the drag started.
+    pub fn begin_gesture_drag(&mut self, kind: GestureDrag, cursor: Vec2) {
+        self.gesture_drag = Some((kind, cursor, cursor));
+    }
+
+    pub fn drag_gesture(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2) {
+        let Some((kind, start, last)) = self.gesture_drag else {
+            return;
+        };
+        if cursor == last {
+            return;
+        }
+        self.gesture_drag = Some((kind, start, cursor));
+        self.gesture(cxt, start, kind.gesture(cursor - last));
+    }
+
+    pub fn end_gesture_drag(&mut self) {
+        self.gesture_drag = None;
+    }
+
+    /// A release over the element the button was pressed on, without dragging, is a click.
+    /// A right click opens the element's context menu at the cursor.
+    pub fn release_button(&mut self, cxt: &mut RwLockWriteGuard<MemState>, button: MouseButton, cursor: Vec2) {
+        let Some(press) = self.buttons.remove(&button) else {
+            return;
+        };
+        let point = self.to_hit_point(cursor);
+        let Some(element) = self.elements.get_mut(&press.node) else {
+            return;
+        };
+        if press.dragged || !element.insection(point) {
+            return;
+        }
+        cxt.begin_event();
+        element.button_click(button, cxt);
+        if button == MouseButton::Right && !cxt.is_default_prevented() {
+            if let Some(menu) = element.get_context_menu() {
+                self.open_context_menu(menu(), cursor);
+            }
+        }
+    }
+
+    /// Run the middle drag callback once the middle button moved past `MemState::drag_threshold`.
+    pub fn drag_buttons(&mut self, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2) {
+        let Some(press) = self.buttons.get_mut(&MouseButton::Middle) else {
+            return;
+        };
+        if !press.dragged && press.start.distance(cursor) <= cxt.drag_threshold {
+            return;
+        }
+        press.dragged = true;
+        cxt.pan_delta = cursor - press.last;
+        press.last = cursor;
+        if cxt.pan_delta == Vec2::ZERO {
+            return;
+        }
+        if let Some(element) = self.elements.get_mut(&press.node) {
+            element.middle_drag(cxt);
+        }
+    }
+
+    /// Show `menu` with its top left corner at `cursor`, replacing the open context menu.
+    pub fn open_context_menu(&mut self, menu: Box<dyn UIElement>, cursor: Vec2) -> NodeId {
+        self.close_context_menu();
+        let element = menu
+            .get_element()
+            .position(Position::Absolute)
+            .inset(Length::Px(cursor.x), Length::Px(cursor.y), Length::Auto, Length::Auto);
+        let root = self.root;
+        let node = self.push_element_with_id(element, root);
+        self.push_children(node, menu.children());
+        self.context_menu = Some(node);
+        node
+    }
+
+    pub fn close_context_menu(&mut self) {
+        if let Some(menu) = self.context_menu.take() {
+            self.remove_element(menu);
+        }
+    }
+
+    pub fn context_menu(&self) -> Option<NodeId> {
+        self.context_menu
+    }
+
+    /// Move the action states on a press or release of the primary pointer. Only the topmost
+    /// hovered interactive element takes a press. A release over the pressed element turns it
+    /// into a `Click`, or a `DoubleClick` when `state` completes one, dispatched along the parent
+    /// chain by `exc_action`. A drag or a release away from it ends in `Release` without a click.
     pub fn update_input_state(&mut self, state: UIMouseState) {
-        for (_, element) in self.elements.iter_mut() {
-            element.set_action_state(state.clone());
+        let target = match state {
+            UIMouseState::Pressed => self.topmost(|element| element.render_state == UIMouseState::Hover && element.is_interactive()),
+            _ => None,
+        };
+        for (node, element) in self.elements.iter_mut() {
+            let hovered = element.render_state == UIMouseState::Hover;
+            match (state, element.get_action_state()) {
+                // a drag or a press released away from the element ends without a click
+                (UIMouseState::Release | UIMouseState::DoubleClick, UIMouseState::Drag) => element.action_state = UIMouseState::Release,
+                (UIMouseState::Release | UIMouseState::DoubleClick, UIMouseState::Pressed) if !hovered => element.action_state = UIMouseState::Release,
+                // a press released over the element is a click
+                (UIMouseState::Release, UIMouseState::Pressed) => element.set_action_state(UIMouseState::Click),
+                (UIMouseState::DoubleClick, UIMouseState::Pressed) => element.set_action_state(UIMouseState::DoubleClick),
+                (UIMouseState::DoubleClick, _) => element.set_action_state(UIMouseState::Release),
+                (UIMouseState::Pressed, _) if Some(*node) != target => {}
+                (state, _) => element.set_action_state(state),
+            }
         }
     }
 
     pub fn test(&mut self) {
         println!("test");
     }
-}
\ No newline at end of file
+}
+
+/// Element of `node` taken out of `removed`, carrying the elements of its `children`.
+fn detach(node: NodeId, removed: &mut HashMap<NodeId, Element>, children: &HashMap<NodeId, Vec<NodeId>>) -> Option<Element> {
+    let mut element = removed.remove(&node)?;
+    element.detached = children
+        .get(&node)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| detach(*child, removed, children))
+        .collect();
+    Some(element)
+}
+
+/// Keeps the content of a virtual list as tall as all of its rows.
+fn virtual_spacer(count: usize, row_height: f32) -> Element {
+    element()
+        .title("virtual list spacer")
+        .width(Length::Px(0.))
+        .height(Length::Px(count as f32 * row_height))
+        .shrink(0.)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, RwLock};
+
+    use bevy::{color::Srgba, input::mouse::{MouseScrollUnit, MouseWheel}, prelude::Entity};
+
+    use crate::drag_drop::DragPayload;
+    use crate::inspector::ColorChannel;
+    use crate::shape::clip_polygon;
+    use crate::selection::SelectMode;
+    use crate::components::{element, element::{Anchor, ElementType, FlexDirection, FlexWrap, GridTrack, Length, Overflow, Position, ScrollAxes}, stacks::{grid, hstack}, virtual_list};
+
+    use super::*;
+
+    #[test]
+    fn test_batch_defers_layout() {
+        let mut layouts = UILayouts::new();
+        layouts.begin_batch();
+        let node = layouts.push_element(element().size(Vec2::new(10., 20.)));
+        layouts.push_element_with_id(element().size(Vec2::new(5., 5.)), node);
+        assert!(layouts.is_dirty());
+        layouts.commit();
+        assert!(!layouts.is_dirty());
+        assert_eq!(layouts.taffy.layout(node).unwrap().size.width, 10.);
+    }
+
+    #[test]
+    fn test_push_outside_batch() {
+        let mut layouts = UILayouts::new();
+        layouts.push_element(element().size(Vec2::new(10., 20.)));
+        assert!(!layouts.is_dirty());
+    }
+
+    #[test]
+    fn test_remove_element() {
+        let mut layouts = UILayouts::new();
+        let node = layouts.push_element(element());
+        let child = layouts.push_element_with_id(element(), node);
+        layouts.remove_element(node);
+        assert_eq!(layouts.iter().count(), 1);
+        assert_eq!(layouts.taffy.total_node_count(), 1);
+        assert!(layouts.replace_element(child, element()).is_none());
+    }
+
+    #[test]
+    fn test_move_and_swap_element() {
+        let mut layouts = UILayouts::new();
+        let a = layouts.push_element(element());
+        let b = layouts.push_element(element());
+        let c = layouts.push_element_with_id(element(), a);
+        assert!(layouts.move_element(a, c, 0).is_err());
+        layouts.move_element(c, b, 0).unwrap();
+        assert_eq!(layouts.parent(c), Some(b));
+        layouts.swap_elements(a, b).unwrap();
+        assert_eq!(layouts.taffy.children(layouts.root).unwrap(), vec![b, a]);
+    }
+
+    #[test]
+    fn test_element_handle() {
+        let mut layouts = UILayouts::new();
+        let item = element().title("item").key("first");
+        let node = layouts.push_element(item.clone());
+        let clone_node = layouts.push_element(item.clone().key("second"));
+        let handle = layouts.find_by_key("first").unwrap();
+        assert_eq!(layouts.node(handle), Some(node));
+        assert_eq!(layouts.find_by_title("item").len(), 2);
+        assert_ne!(layouts.handle(node), layouts.handle(clone_node));
+
+        let removed = layouts.remove_element(node).unwrap();
+        assert!(layouts.get(handle).is_none());
+        let node = layouts.push_element(removed);
+        assert_eq!(layouts.handle(node), Some(handle));
+        assert!(layouts.get_mut(handle).is_some());
+
+        // the descendants come back with their handles and keys
+        let parent = layouts.push_element(element());
+        let child = layouts.push_element_with_id(element().key("child"), parent);
+        let grandchild = layouts.push_element_with_id(element().title("grandchild"), child);
+        let (child_handle, grandchild_handle) = (layouts.handle(child).unwrap(), layouts.handle(grandchild).unwrap());
+        let removed = layouts.remove_element(parent).unwrap();
+        assert!(layouts.get(child_handle).is_none());
+        assert!(layouts.find_by_key("child").is_none());
+        let parent = layouts.push_element(removed);
+        let child = layouts.node(child_handle).unwrap();
+        assert_eq!(layouts.parent(child), Some(parent));
+        assert_eq!(layouts.find_by_key("child"), Some(child_handle));
+        assert_eq!(layouts.parent(layouts.node(grandchild_handle).unwrap()), Some(child));
+        assert_eq!(layouts.get(grandchild_handle).unwrap().get_title(), "grandchild");
+    }
+
+    #[test]
+    fn test_duplicate_key() {
+        let mut layouts = UILayouts::new();
+        let first = layouts.push_element(element().key("same"));
+        let second = layouts.push_element(element().key("same"));
+        assert_eq!(layouts.find_by_key("same"), layouts.handle(second));
+
+        // removing the element that lost the key keeps it on the other one
+        layouts.remove_element(first);
+        assert_eq!(layouts.find_by_key("same"), layouts.handle(second));
+    }
+
+    #[test]
+    fn test_restyle_after_update() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        layouts.push_element(element().element_type(ElementType::Debug));
+        let node = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).shrink(0.), content);
+        let handle = layouts.handle(node).unwrap();
+        let origin = Vec3::new(-400., 300., 0.);
+        layouts.update(&mut cxt, origin);
+        assert_eq!(layouts.layout(handle).unwrap().size.width, 50.);
+
+        // same window size, only the edit made through the handle dirties the tree
+        layouts.get_mut(handle).unwrap().apply_edit(InspectorField::Width, 10.);
+        layouts.update(&mut cxt, origin);
+        assert_eq!(layouts.layout(handle).unwrap().size.width, 60.);
+    }
+
+    #[test]
+    fn test_debug_views() {
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        layouts.push_element_with_id(element(), content);
+        let panel = layouts.push_element(element().element_type(ElementType::Debug));
+        let sub_panel = layouts.push_element(element().element_type(ElementType::Debug));
+        layouts.set_debug_target(sub_panel, content);
+        layouts.gen_debug_elements_tree();
+        assert_eq!(layouts.debug_views().count(), 2);
+        assert!(layouts.taffy.child_count(panel) > 0);
+        assert!(layouts.taffy.child_count(sub_panel) > 0);
+
+        let count = layouts.iter().count();
+        layouts.toggle_debug_view(sub_panel);
+        assert_eq!(layouts.taffy.child_count(sub_panel), 0);
+        assert!(layouts.iter().count() < count);
+        layouts.gen_debug_elements_tree();
+        assert_eq!(layouts.taffy.child_count(sub_panel), 0);
+    }
+
+    #[test]
+    fn test_sync_debug_views() {
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let child = layouts.push_element_with_id(element(), content);
+        let panel = layouts.push_element(element().element_type(ElementType::Debug));
+        layouts.gen_debug_elements_tree();
+        let count = layouts.iter().count();
+
+        let added = layouts.push_element_with_id(element(), child);
+        layouts.sync_debug_views();
+        assert!(layouts.iter().count() > count + 1);
+        assert!(layouts.debug_views[&panel].debuge_relations.contains_key(&added));
+
+        layouts.remove_element(child);
+        layouts.sync_debug_views();
+        assert!(!layouts.debug_views[&panel].debuge_relations.contains_key(&child));
+        assert!(layouts.iter().count() < count);
+        assert!(layouts.debug_views[&panel].debuge_relations.contains_key(&content));
+    }
+
+    #[test]
+    fn test_inspector() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let node = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).color(Srgba::RED).background_color(Srgba::WHITE), content);
+
+        cxt.inspected = layouts.handle(node).unwrap().id();
+        cxt.inspector_edits.push((InspectorField::Width, 10.));
+        cxt.inspector_edits.push((InspectorField::Color(ColorChannel::Hue), 120.));
+        cxt.inspector_edits.push((InspectorField::BackgroundColor(ColorChannel::Lightness), -0.5));
+        cxt.inspector_edits.push((InspectorField::BackgroundColor(ColorChannel::Alpha), -0.5));
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+        assert!(layouts.inspector.is_some());
+        assert_eq!(layouts.taffy.layout(node).unwrap().size.width, 60.);
+        let color = layouts.elements[&node].get_color();
+        assert!(color.red < 1e-4 && (color.green - 1.).abs() < 1e-4);
+        // white turns gray and translucent
+        let background = layouts.elements[&node].get_background_color();
+        assert!((background.red - 0.5).abs() < 1e-4 && (background.alpha - 0.5).abs() < 1e-4);
+
+        cxt.inspected = -1;
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+        assert!(layouts.inspector.is_none());
+    }
+
+    #[test]
+    fn test_inspector_target_removed() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let origin = Vec3::new(-400., 300., 0.);
+        let content = layouts.push_element(element());
+        let list = layouts.push_element_with_id(
+            virtual_list(10, 20., |index| element().title(&format!("row {}", index))).size(Vec2::new(200., 100.)),
+            content,
+        );
+        layouts.update(&mut cxt, origin);
+        layouts.update(&mut cxt, origin);
+        let row = layouts.virtual_lists[&list].rows[&5];
+
+        // shrinking the list drops the inspected row in the same update
+        cxt.inspected = layouts.handle(row).unwrap().id();
+        layouts.set_row_count(list, 3);
+        layouts.update(&mut cxt, origin);
+        assert!(layouts.inspector.is_none());
+        assert!(layouts.element(row).is_none());
+        assert_eq!(cxt.inspected, -1);
+    }
+
+    #[test]
+    fn test_input_state() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let button = layouts.push_element_with_id(element().size(Vec2::new(100., 50.)).shrink(0.).click(|_, _| {}), content);
+        let origin = Vec3::new(-400., 300., 0.);
+        let mut move_to = |layouts: &mut UILayouts, x: f32| {
+            cxt.mouse_position = Vec2::new(x, 25.);
+            layouts.update(&mut cxt, origin);
+        };
+
+        move_to(&mut layouts, 50.);
+        layouts.update_input_state(UIMouseState::Pressed);
+        assert_eq!(layouts.elements[&button].action_state, UIMouseState::Pressed);
+        layouts.update_input_state(UIMouseState::Release);
+        assert_eq!(layouts.elements[&button].action_state, UIMouseState::Click);
+
+        move_to(&mut layouts, 50.);
+        layouts.update_input_state(UIMouseState::Pressed);
+        layouts.update_input_state(UIMouseState::DoubleClick);
+        assert_eq!(layouts.elements[&button].action_state, UIMouseState::DoubleClick);
+
+        // released away from the element
+        move_to(&mut layouts, 50.);
+        layouts.update_input_state(UIMouseState::Pressed);
+        move_to(&mut layouts, 500.);
+        layouts.update_input_state(UIMouseState::Release);
+        assert_eq!(layouts.elements[&button].action_state, UIMouseState::Release);
+
+        // a press outside never reaches the element
+        layouts.update_input_state(UIMouseState::Pressed);
+        assert_eq!(layouts.elements[&button].action_state, UIMouseState::Release);
+    }
+
+    #[test]
+    fn test_grid_layout() {
+        let mut layouts = UILayouts::new();
+        grid((element(), element(), element().grid_column(1, 2)))
+            .size(Vec2::new(300., 200.))
+            .grid_template_columns(vec![GridTrack::Length(100.), GridTrack::Fr(1.)])
+            .grid_template_rows(vec![GridTrack::Length(50.), GridTrack::Length(50.)])
+            .gap(Vec2::new(10., 10.))
+            .push_to_layout(&mut layouts);
+
+        let container = layouts.taffy.child_at_index(layouts.root, 0).unwrap();
+        let children = layouts.taffy.children(container).unwrap();
+        assert_eq!(layouts.taffy.layout(children[1]).unwrap().location.x, 110.);
+        assert_eq!(layouts.taffy.layout(children[2]).unwrap().location.y, 60.);
+        assert_eq!(layouts.taffy.layout(children[2]).unwrap().size.width, 300.);
+    }
+
+    #[test]
+    fn test_flex_properties() {
+        let mut layouts = UILayouts::new();
+        hstack((
+            element().width(Length::Percent(0.5)).height(Length::Px(10.)).max_size(Length::Px(150.), Length::Auto),
+            element().grow(1.).height(Length::Px(10.)),
+            element().basis(Length::Px(300.)).aspect_ratio(2.),
+        ))
+        .width(Length::Px(400.))
+        .wrap(FlexWrap::Wrap)
+        .gap(Vec2::new(0., 5.))
+        .push_to_layout(&mut layouts);
+
+        let container = layouts.taffy.child_at_index(layouts.root, 0).unwrap();
+        let children = layouts.taffy.children(container).unwrap();
+        let layout = |node| *layouts.taffy.layout(node).unwrap();
+        assert_eq!(layout(children[0]).size.width, 150.);
+        assert_eq!(layout(children[1]).size.width, 250.);
+        assert_eq!(layout(children[2]).size.width, 300.);
+        assert_eq!(layout(children[2]).size.height, 150.);
+        assert_eq!(layout(children[2]).location.y, 15.);
+    }
+
+    #[test]
+    fn test_responsive_shape_size() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let node = layouts.push_element_with_id(rectangle().width(Length::Percent(0.5)).height(Length::Px(10.)), content);
+        let shape_size = |layouts: &UILayouts| layouts.element(node).unwrap().shape.as_ref().unwrap().read().unwrap().get_size();
+
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+        assert_eq!(shape_size(&layouts), Some(Vec2::new(400., 10.)));
+        // the shape follows the window when it resizes
+        layouts.update(&mut cxt, Vec3::new(-200., 300., 0.));
+        assert_eq!(shape_size(&layouts), Some(Vec2::new(200., 10.)));
+    }
+
+    #[test]
+    fn test_absolute_anchor() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let panel = layouts.push_element_with_id(element().size(Vec2::new(200., 100.)), content);
+        let flow = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)), panel);
+        let center = layouts.push_element_with_id(element().size(Vec2::new(20., 20.)).anchor(Anchor::Center, Vec2::ZERO), panel);
+        let corner = layouts.push_element_with_id(element().size(Vec2::new(20., 20.)).anchor(Anchor::BottomRight, Vec2::splat(5.)), panel);
+        let shifted = layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(20., 20.))
+                .position(Position::Relative)
+                .inset(Length::Px(10.), Length::Auto, Length::Auto, Length::Auto),
+            panel,
+        );
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+
+        assert_eq!(layouts.taffy.layout(flow).unwrap().location.x, 0.);
+        assert_eq!(layouts.taffy.layout(shifted).unwrap().location.x, 60.);
+        assert_eq!(layouts.taffy.layout(corner).unwrap().location, taffy::Point { x: 175., y: 75. });
+        let panel_center = layouts.element(panel).unwrap().get_rect().center();
+        assert_eq!(layouts.element(center).unwrap().get_rect().center(), panel_center);
+
+        // the layout reports the drawn position
+        let handle = layouts.handle(center).unwrap();
+        assert_eq!(layouts.layout(handle).unwrap().location, taffy::Point { x: 90., y: 40. });
+        assert_eq!(layouts.rect(handle).unwrap().min, Vec2::new(-400. + 90., 300. - 40. - 20.));
+
+        let unanchored = element().anchor(Anchor::Center, Vec2::ZERO).position(Position::Relative);
+        assert!(unanchored.anchor_margin(taffy::Size { width: 20., height: 20. }).is_none());
+    }
+
+    #[test]
+    fn test_clipped_shapes() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let card = layouts.push_element_with_id(
+            element().size(Vec2::new(100., 100.)).direction(FlexDirection::Column).overflow(Overflow::Hidden),
+            content,
+        );
+        let rows: Vec<_> = [80., 50., 50.]
+            .into_iter()
+            .map(|height| layouts.push_element_with_id(rectangle().size(Vec2::new(100., height)).shrink(0.), card))
+            .collect();
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+
+        // a shape stays while any part of it is inside, not only its center
+        let straddling = layouts.element(rows[1]).unwrap();
+        let clip = straddling.clip.unwrap();
+        assert!(!clip.contains(straddling.get_rect().center()));
+        assert!(straddling.shape_visible());
+        assert!(!layouts.element(rows[2]).unwrap().shape_visible());
+
+        // shapes that can't be cut by a rect are drawn as their outline cut to the clip
+        let square = [Vec2::new(-10., -10.), Vec2::new(10., -10.), Vec2::new(10., 10.), Vec2::new(-10., 10.)];
+        let cut = clip_polygon(&square, Rect::new(0., -20., 20., 20.));
+        assert_eq!(cut, vec![Vec2::new(0., -10.), Vec2::new(10., -10.), Vec2::new(10., 10.), Vec2::new(0., 10.)]);
+        assert!(clip_polygon(&square, Rect::new(20., 20., 30., 30.)).is_empty());
+    }
+
+    #[test]
+    fn test_scroll_view() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let origin = Vec3::new(-400., 300., 0.);
+        let content = layouts.push_element(element());
+        let view = layouts.push_element_with_id(
+            element().size(Vec2::new(100., 100.)).direction(FlexDirection::Column).scroll(ScrollAxes::Vertical),
+            content,
+        );
+        let rows: Vec<NodeId> = (0..5)
+            .map(|_| layouts.push_element_with_id(element().size(Vec2::new(80., 50.)).shrink(0.), view))
+            .collect();
+        layouts.update(&mut cxt, origin);
+        let top = layouts.element(rows[0]).unwrap().get_rect();
+
+        assert!(layouts.scroll(Vec2::new(50., 50.), Vec2::new(0., 1000.)));
+        layouts.update(&mut cxt, origin);
+        let element = layouts.element(view).unwrap();
+        assert_eq!(element.max_scroll(), Vec2::new(0., 150.));
+        assert_eq!(element.get_scroll_offset(), element.max_scroll());
+
+        let row = layouts.element(rows[0]).unwrap();
+        let center = row.get_rect().center();
+        assert_eq!(center.y, top.center().y + 150.);
+        assert!(!row.insection(Vec2::new(center.x, -center.y)));
+        let last = layouts.element(rows[4]).unwrap().get_rect().center();
+        assert!(layouts.element(rows[4]).unwrap().insection(Vec2::new(last.x, -last.y)));
+
+        assert!(!layouts.scroll(Vec2::new(500., 500.), Vec2::new(0., -10.)));
+        assert!(!layouts.scroll(Vec2::new(50., 50.), Vec2::new(0., 10.)));
+    }
+
+    #[test]
+    fn test_scrollbar_z_order() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let panel = |left: f32| {
+            element()
+                .size(Vec2::new(100., 100.))
+                .position(Position::Absolute)
+                .inset(Length::Px(left), Length::Px(0.), Length::Auto, Length::Auto)
+        };
+        let view = layouts.push_element_with_id(panel(0.).direction(FlexDirection::Column).scroll(ScrollAxes::Vertical), content);
+        for _ in 0..5 {
+            layouts.push_element_with_id(element().size(Vec2::new(80., 50.)).shrink(0.), view);
+        }
+        let cover = layouts.push_element_with_id(panel(90.), content);
+        let origin = Vec3::new(-400., 300., 0.);
+        layouts.update(&mut cxt, origin);
+
+        // the panel drawn above the scrollbar takes the press
+        assert!(!layouts.begin_scroll_drag(Vec2::new(96., 50.)));
+        layouts.send_to_back(cover);
+        layouts.update(&mut cxt, origin);
+        assert!(layouts.begin_scroll_drag(Vec2::new(96., 50.)));
+        assert_eq!(layouts.scroll_drag.map(|(node, vertical, _)| (node, vertical)), Some((view, true)));
+    }
+
+    #[test]
+    fn test_virtual_list() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let origin = Vec3::new(-400., 300., 0.);
+        let content = layouts.push_element(element());
+        let list = layouts.push_element_with_id(
+            virtual_list(100_000, 20., |index| element().title(&format!("row {}", index))).size(Vec2::new(200., 100.)),
+            content,
+        );
+        layouts.update(&mut cxt, origin);
+        layouts.update(&mut cxt, origin);
+        let built = layouts.virtual_lists[&list].rows.len();
+        assert_eq!(built, 100 / 20 + VIRTUAL_OVERSCAN);
+        let count = layouts.iter().count();
+        let nodes: HashSet<NodeId> = layouts.virtual_lists[&list].rows.values().copied().collect();
+
+        assert!(layouts.scroll(Vec2::new(100., 50.), Vec2::new(0., 1000.)));
+        layouts.update(&mut cxt, origin);
+        let rows = &layouts.virtual_lists[&list].rows;
+        assert_eq!(rows.len(), 100 / 20 + VIRTUAL_OVERSCAN * 2);
+        assert_eq!(layouts.element(rows[&50]).unwrap().get_title(), "row 50");
+        assert_eq!(layouts.taffy.layout(rows[&50]).unwrap().location.y, 1000.);
+        assert!(nodes.iter().all(|node| rows.values().any(|row| row == node)));
+        assert_eq!(layouts.iter().count(), count + VIRTUAL_OVERSCAN);
+
+        layouts.set_row_count(list, 10);
+        layouts.update(&mut cxt, origin);
+        layouts.update(&mut cxt, origin);
+        assert!(layouts.virtual_lists[&list].rows.keys().all(|index| *index < 10));
+        assert_eq!(layouts.element(list).unwrap().get_scroll_offset().y, 100.);
+    }
+
+    #[test]
+    fn test_overflow_clip() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let card = layouts.push_element_with_id(
+            element().size(Vec2::new(100., 100.)).round(20.).overflow(Overflow::ClipRounded),
+            content,
+        );
+        let fill = layouts.push_element_with_id(element().size(Vec2::new(100., 100.)).shrink(0.), card);
+        let outside = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).shrink(0.), card);
+        let hidden = layouts.push_element_with_id(element().size(Vec2::new(10., 10.)).overflow(Overflow::Hidden), content);
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+
+        assert_eq!(layouts.taffy.style(card).unwrap().overflow.x, taffy::Overflow::Hidden);
+        assert_eq!(layouts.taffy.style(hidden).unwrap().overflow.y, taffy::Overflow::Hidden);
+        let fill = layouts.element(fill).unwrap();
+        let rect = fill.get_rect();
+        assert!(fill.insection(Vec2::new(rect.center().x, -rect.center().y)));
+        assert!(!fill.insection(Vec2::new(rect.min.x + 2., -(rect.max.y - 2.))));
+        assert!(fill.insection(Vec2::new(rect.min.x + 2., -rect.center().y)));
+        let outside = layouts.element(outside).unwrap();
+        let center = outside.get_rect().center();
+        assert!(!outside.insection(Vec2::new(center.x, -center.y)));
+
+        // partial fills are rounded where they reach into a corner, each corner on its own
+        let clip = layouts.element(card).unwrap().child_clip().unwrap();
+        let top_left = Vec2::new(clip.rect.min.x, clip.rect.max.y);
+        let below = Rect::from_corners(top_left - Vec2::new(0., 5.), clip.rect.center());
+        assert_eq!(clip.fit(below).z, 15.);
+        let inset = Rect::from_corners(top_left + Vec2::new(4., -2.), clip.rect.center());
+        assert_eq!(clip.fit(inset).z, 10.);
+        assert_eq!(clip.fit(inset).x, 0.);
+        let clip = Clip { radii: Vec4::new(0., 0., 20., 0.), ..clip };
+        assert!(!clip.contains(top_left + Vec2::new(1., -1.)));
+        assert!(clip.contains(Vec2::new(clip.rect.max.x - 1., clip.rect.max.y - 1.)));
+    }
+
+    #[test]
+    fn test_focus_order() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let origin = Vec3::new(-400., 300., 0.);
+        let content = layouts.push_element(element());
+        let a = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).tab_index(0), content);
+        let b = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).tab_index(2), content);
+        let c = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).tab_index(-1), content);
+        let d = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).focusable(true), content);
+        layouts.update(&mut cxt, origin);
+        assert_eq!(layouts.focus_order(), vec![b, a, d]);
+
+        layouts.focus_next(&mut cxt, false);
+        assert_eq!(layouts.focused(&cxt), Some(b));
+        layouts.focus_next(&mut cxt, false);
+        layouts.focus_next(&mut cxt, false);
+        assert_eq!(layouts.focused(&cxt), Some(d));
+        assert!(layouts.element(d).unwrap().is_focused());
+        layouts.focus_next(&mut cxt, false);
+        assert_eq!(layouts.focused(&cxt), Some(b));
+        layouts.focus_next(&mut cxt, true);
+        assert_eq!(layouts.focused(&cxt), Some(d));
+        assert!(!layouts.element(b).unwrap().is_focused());
+
+        layouts.focus_at(&mut cxt, Vec2::new(125., 25.));
+        assert_eq!(layouts.focused(&cxt), Some(c));
+        layouts.focus_at(&mut cxt, Vec2::new(500., 500.));
+        assert_eq!(layouts.focused(&cxt), None);
+
+        layouts.focus(&mut cxt, Some(a));
+        layouts.remove_element(a);
+        layouts.update(&mut cxt, origin);
+        assert_eq!(cxt.focused, -1);
+    }
+
+    #[test]
+    fn test_key_input() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let typed = Arc::new(Mutex::new(String::new()));
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let panel = layouts.push_element_with_id(element(), content);
+        let typed_in = typed.clone();
+        let input = layouts.push_element_with_id(
+            element().focusable(true).on_text_input(move |_, text, _| typed_in.lock().unwrap().push_str(text)),
+            panel,
+        );
+        let button = layouts.push_element_with_id(element().focusable(true), content);
+        for (shortcut, scope, name) in [
+            (Shortcut::new(KeyCode::KeyZ).ctrl(), None, "global"),
+            (Shortcut::new(KeyCode::KeyZ).ctrl(), Some(panel), "panel"),
+            (Shortcut::new(KeyCode::KeyD), None, "delete"),
+        ] {
+            let fired = fired.clone();
+            layouts.add_shortcut(shortcut, scope, move |_, _| fired.lock().unwrap().push(name));
+        }
+        let press = |key_code, logical_key| KeyboardInput { key_code, logical_key, state: ButtonState::Pressed, window: Entity::PLACEHOLDER };
+        let mut keys = ButtonInput::default();
+
+        layouts.focus(&mut cxt, Some(input));
+        layouts.key_input(&mut cxt, &press(KeyCode::KeyD, Key::Character("d".into())), &keys);
+        assert_eq!(typed.lock().unwrap().as_str(), "d");
+        keys.press(KeyCode::ControlLeft);
+        layouts.key_input(&mut cxt, &press(KeyCode::KeyZ, Key::Character("z".into())), &keys);
+        layouts.focus(&mut cxt, Some(button));
+        layouts.key_input(&mut cxt, &press(KeyCode::KeyZ, Key::Character("z".into())), &keys);
+        keys.release(KeyCode::ControlLeft);
+        layouts.key_input(&mut cxt, &press(KeyCode::KeyD, Key::Character("d".into())), &keys);
+        assert_eq!(*fired.lock().unwrap(), vec!["panel", "global", "delete"]);
+        assert_eq!(typed.lock().unwrap().as_str(), "d");
+
+        layouts.key_input(&mut cxt, &press(KeyCode::Tab, Key::Tab), &keys);
+        assert_eq!(layouts.focused(&cxt), Some(input));
+        layouts.remove_element(panel);
+        layouts.focus(&mut cxt, Some(button));
+        keys.press(KeyCode::ControlLeft);
+        layouts.key_input(&mut cxt, &press(KeyCode::KeyZ, Key::Character("z".into())), &keys);
+        assert_eq!(fired.lock().unwrap().last(), Some(&"global"));
+
+        // debug keys are opt in
+        keys.release(KeyCode::ControlLeft);
+        layouts.key_input(&mut cxt, &press(KeyCode::KeyB, Key::Character("b".into())), &keys);
+        assert!(!layouts.bounds_overlay());
+        layouts.add_debug_shortcuts();
+        layouts.key_input(&mut cxt, &press(KeyCode::KeyB, Key::Character("b".into())), &keys);
+        assert!(layouts.bounds_overlay());
+    }
+
+    #[test]
+    fn test_key_default() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let (clicked, keyed) = (log.clone(), log.clone());
+        let field = layouts.push_element_with_id(
+            element()
+                .focusable(true)
+                .click(move |_, _| clicked.lock().unwrap().push("click".to_string()))
+                .on_key_down(move |_, event, cxt| {
+                    keyed.lock().unwrap().push(format!("{:?}", event.key_code));
+                    if event.key_code == KeyCode::Enter {
+                        cxt.prevent_default();
+                    }
+                }),
+            content,
+        );
+        let press = |key_code, logical_key| KeyboardInput { key_code, logical_key, state: ButtonState::Pressed, window: Entity::PLACEHOLDER };
+        let keys = ButtonInput::default();
+        layouts.focus(&mut cxt, Some(field));
+
+        // the element sees Enter, Escape and Space first, only Space still activates it
+        layouts.key_input(&mut cxt, &press(KeyCode::Enter, Key::Enter), &keys);
+        layouts.key_input(&mut cxt, &press(KeyCode::Escape, Key::Escape), &keys);
+        layouts.exc_action(&mut cxt);
+        layouts.key_input(&mut cxt, &press(KeyCode::Space, Key::Space), &keys);
+        layouts.exc_action(&mut cxt);
+        assert_eq!(*log.lock().unwrap(), vec!["Enter", "Escape", "Space", "click"]);
+        assert_eq!(layouts.focused(&cxt), Some(field));
+    }
+
+    #[test]
+    fn test_drag_threshold() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let (start, moved, end) = (events.clone(), events.clone(), events.clone());
+        let node = layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(100., 50.))
+                .shrink(0.)
+                .on_drag_start(move |_, _| start.lock().unwrap().push("start"))
+                .on_drag(move |_, _| moved.lock().unwrap().push("drag"))
+                .on_drag_end(move |_, cxt| end.lock().unwrap().push(if cxt.drag_delta.2.x == 30. { "end" } else { "wrong delta" })),
+            content,
+        );
+        let origin = Vec3::new(-400., 300., 0.);
+        cxt.mouse_position = Vec2::new(50., 25.);
+        layouts.update(&mut cxt, origin);
+        layouts.update_input_state(UIMouseState::Pressed);
+        cxt.drag_delta = (Vec2::new(50., 25.), Vec2::new(52., 25.), Vec2::ZERO);
+        layouts.update(&mut cxt, origin);
+        layouts.exc_action(&mut cxt);
+        assert_eq!(layouts.element(node).unwrap().action_state, UIMouseState::Pressed);
+        assert!(events.lock().unwrap().is_empty());
+
+        for x in [70., 80.] {
+            cxt.mouse_position.x = x;
+            cxt.drag_delta.1.x = x;
+            layouts.update(&mut cxt, origin);
+            layouts.exc_action(&mut cxt);
+        }
+        assert!(layouts.element(node).unwrap().is_dragging());
+        cxt.drag_delta.2 = cxt.drag_delta.1 - cxt.drag_delta.0;
+        layouts.update_input_state(UIMouseState::Release);
+        layouts.exc_action(&mut cxt);
+        assert_eq!(*events.lock().unwrap(), vec!["start", "drag", "end"]);
+        assert_eq!(layouts.element(node).unwrap().action_state, UIMouseState::Release);
+    }
+
+    #[test]
+    fn test_double_click() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let clicks = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let (single, double) = (clicks.clone(), clicks.clone());
+        layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(100., 50.))
+                .shrink(0.)
+                .click(move |_, _| single.lock().unwrap().push("click"))
+                .double_click(move |_, _| double.lock().unwrap().push("double")),
+            content,
+        );
+        cxt.mouse_position = Vec2::new(50., 25.);
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+        for time in [1., 1.2, 1.3, 2.] {
+            cxt.time = time;
+            layouts.update_input_state(UIMouseState::Pressed);
+            let state = match cxt.register_click() {
+                true => UIMouseState::DoubleClick,
+                false => UIMouseState::Release,
+            };
+            layouts.update_input_state(state);
+            layouts.exc_action(&mut cxt);
+        }
+        assert_eq!(*clicks.lock().unwrap(), vec!["click", "double", "click", "click"]);
+    }
+
+    #[test]
+    fn test_click_dispatch() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let record = |name: &'static str| {
+            let log = log.clone();
+            move |_: &mut Element, _: &mut RwLockWriteGuard<MemState>| log.lock().unwrap().push(name)
+        };
+        // a higher z-order element pushed before the outer one, away from the inner one
+        layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(50., 50.))
+                .position(Position::Absolute)
+                .inset(Length::Px(150.), Length::Px(0.), Length::Auto, Length::Auto)
+                .order(5)
+                .click(record("badge")),
+            content,
+        );
+        let outer = layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(200., 100.))
+                .shrink(0.)
+                .click(record("outer"))
+                .on_click_capture(record("outer capture")),
+            content,
+        );
+        // only takes right clicks, left presses still stop on it
+        layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(50., 40.))
+                .position(Position::Absolute)
+                .inset(Length::Px(0.), Length::Px(60.), Length::Auto, Length::Auto)
+                .order(5)
+                .on_right_click(|_, _| {}),
+            content,
+        );
+        let stop = Arc::new(Mutex::new(false));
+        let inner_log = log.clone();
+        let inner_stop = stop.clone();
+        layouts.push_element_with_id(
+            element().size(Vec2::new(100., 50.)).shrink(0.).click(move |_, cxt| {
+                inner_log.lock().unwrap().push("inner");
+                if *inner_stop.lock().unwrap() {
+                    cxt.stop_propagation();
+                }
+            }),
+            outer,
+        );
+        let origin = Vec3::new(-400., 300., 0.);
+        let click = |layouts: &mut UILayouts, cxt: &mut RwLockWriteGuard<MemState>, cursor: Vec2| {
+            cxt.mouse_position = cursor;
+            layouts.update(cxt, origin);
+            layouts.update_input_state(UIMouseState::Pressed);
+            layouts.update_input_state(UIMouseState::Release);
+            layouts.exc_action(cxt);
+        };
+
+        click(&mut layouts, &mut cxt, Vec2::new(50., 25.));
+        assert_eq!(*log.lock().unwrap(), vec!["outer capture", "inner", "outer"]);
+        log.lock().unwrap().clear();
+        *stop.lock().unwrap() = true;
+        click(&mut layouts, &mut cxt, Vec2::new(50., 25.));
+        assert_eq!(*log.lock().unwrap(), vec!["outer capture", "inner"]);
+        log.lock().unwrap().clear();
+        click(&mut layouts, &mut cxt, Vec2::new(175., 25.));
+        assert_eq!(*log.lock().unwrap(), vec!["badge"]);
+        log.lock().unwrap().clear();
+        click(&mut layouts, &mut cxt, Vec2::new(25., 80.));
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_z_order() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let panel = |left: f32| {
+            element()
+                .size(Vec2::new(100., 100.))
+                .position(Position::Absolute)
+                .inset(Length::Px(left), Length::Px(0.), Length::Auto, Length::Auto)
+        };
+        let back = layouts.push_element_with_id(panel(0.).order(3), content);
+        let back_child = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).order(100), back);
+        let front = layouts.push_element_with_id(panel(20.), content);
+        assert_eq!(layouts.paint_order(), vec![layouts.root, content, front, back, back_child]);
+
+        let origin = Vec3::new(-400., 300., 0.);
+        cxt.mouse_position = Vec2::new(40., 20.);
+        layouts.update(&mut cxt, origin);
+        let hovered = |layouts: &UILayouts, node| layouts.element(node).unwrap().render_state == UIMouseState::Hover;
+        assert!(hovered(&layouts, back_child) && hovered(&layouts, back) && hovered(&layouts, content));
+        assert!(!hovered(&layouts, front));
+
+        // the child's z-order only counts among its siblings
+        layouts.bring_to_front(front);
+        assert_eq!(layouts.paint_order(), vec![layouts.root, content, back, back_child, front]);
+        layouts.update(&mut cxt, origin);
+        assert!(hovered(&layouts, front) && !hovered(&layouts, back_child));
+
+        layouts.send_to_back(front);
+        assert_eq!(layouts.element(front).unwrap().get_z_order(), 2);
+        assert_eq!(layouts.paint_order()[2], front);
+    }
+
+    #[test]
+    fn test_depth() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let panel = |left: f32| {
+            element()
+                .size(Vec2::new(100., 100.))
+                .position(Position::Absolute)
+                .inset(Length::Px(left), Length::Px(0.), Length::Auto, Length::Auto)
+        };
+        let back = layouts.push_element_with_id(panel(0.).direction(FlexDirection::Column).scroll(ScrollAxes::Vertical), content);
+        let label = layouts.push_element_with_id(text("label").size(Vec2::new(80., 200.)), back);
+        let front = layouts.push_element_with_id(panel(20.), content);
+        let origin = Vec3::new(-400., 300., 0.);
+        layouts.update(&mut cxt, origin);
+        let depth = |layouts: &UILayouts, node| layouts.element(node).unwrap().layout_anchor.z;
+        let scrollbars = |layouts: &UILayouts| layouts.element(back).unwrap().scrollbar_depth;
+
+        // the text of the back panel and its scrollbars stay under the front panel
+        layouts.bring_to_front(front);
+        layouts.update(&mut cxt, origin);
+        let label_text = layouts.element(label).unwrap().shape_anchor().z;
+        assert!(depth(&layouts, back) < label_text && label_text < scrollbars(&layouts));
+        assert!(scrollbars(&layouts) < depth(&layouts, front));
+        assert!(depth(&layouts, front) < layouts.top_depth());
+
+        layouts.bring_to_front(back);
+        layouts.update(&mut cxt, origin);
+        assert!(depth(&layouts, front) < depth(&layouts, back));
+        assert!(layouts.element(label).unwrap().shape_anchor().z < scrollbars(&layouts));
+    }
+
+    #[test]
+    fn test_drag_and_drop() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let source = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).shrink(0.).drag_source("add node".to_string()), content);
+        let rejecting = layouts.push_element_with_id(element().size(Vec2::new(100., 100.)).shrink(0.).accepts::<i32>(), content);
+        let record = |name: &'static str| {
+            let log = log.clone();
+            move |_: &mut Element, payload: &DragPayload, _: &mut RwLockWriteGuard<MemState>| {
+                log.lock().unwrap().push(format!("{} {}", name, payload.get::<String>().unwrap()))
+            }
+        };
+        let canvas = layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(100., 100.))
+                .shrink(0.)
+                .accepts::<String>()
+                .on_drag_enter(record("enter"))
+                .on_drag_over(record("over"))
+                .on_drag_leave(record("leave"))
+                .on_drop(record("drop")),
+            content,
+        );
+        layouts.push_element_with_id(text("canvas").size(Vec2::new(60., 20.)), canvas);
+        let origin = Vec3::new(-400., 300., 0.);
+        let move_to = |layouts: &mut UILayouts, cxt: &mut RwLockWriteGuard<MemState>, x: f32| {
+            cxt.mouse_position = Vec2::new(x, 25.);
+            cxt.drag_delta.1 = cxt.mouse_position;
+            layouts.update(cxt, origin);
+        };
+
+        move_to(&mut layouts, &mut cxt, 25.);
+        layouts.update_input_state(UIMouseState::Pressed);
+        cxt.drag_delta.0 = cxt.mouse_position;
+        move_to(&mut layouts, &mut cxt, 100.);
+        assert!(cxt.drag_payload.as_ref().is_some_and(|payload| payload.is::<String>()));
+        assert_eq!(layouts.element(rejecting).unwrap().drop_state, Some(false));
+        assert!(layouts.drop_target().is_none());
+
+        move_to(&mut layouts, &mut cxt, 200.);
+        assert_eq!(layouts.drop_target(), Some(canvas));
+        assert_eq!(layouts.element(canvas).unwrap().drop_state, Some(true));
+        assert_eq!(layouts.element(rejecting).unwrap().drop_state, None);
+        // the ghost is drawn above every element and its text
+        let highest = layouts.elements.values().map(|element| element.shape_anchor().z).fold(f32::MIN, f32::max);
+        assert!(layouts.ghost_depth() > highest && layouts.ghost_depth() > layouts.top_depth());
+        move_to(&mut layouts, &mut cxt, 100.);
+        move_to(&mut layouts, &mut cxt, 210.);
+        layouts.update_input_state(UIMouseState::Release);
+        layouts.update(&mut cxt, origin);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["enter add node", "over add node", "leave add node", "enter add node", "over add node", "drop add node"]
+        );
+        assert!(cxt.drag_payload.is_none());
+        assert!(layouts.drop_target().is_none());
+        assert_eq!(layouts.element(canvas).unwrap().drop_state, None);
+        assert_eq!(layouts.element(source).unwrap().action_state, UIMouseState::Release);
+    }
+
+    #[test]
+    fn test_selection() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let changes = Arc::new(Mutex::new(0));
+        let content = layouts.push_element(element());
+        let nodes: Vec<NodeId> = (0..3)
+            .map(|_| {
+                let changes = changes.clone();
+                let node = element()
+                    .size(Vec2::new(50., 50.))
+                    .shrink(0.)
+                    .selectable(true)
+                    .on_selection_change(move |_, _| *changes.lock().unwrap() += 1);
+                layouts.push_element_with_id(node, content)
+            })
+            .collect();
+        let label = layouts.push_element_with_id(element().size(Vec2::new(20., 10.)), nodes[2]);
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+
+        assert!(layouts.press_selection(&mut cxt, Vec2::new(25., 25.), SelectMode::Replace));
+        assert_eq!(layouts.selection(&cxt), vec![nodes[0]]);
+        assert_eq!(layouts.element(nodes[0]).unwrap().render_state, UIMouseState::Selected);
+        layouts.press_selection(&mut cxt, Vec2::new(105., 5.), SelectMode::Extend);
+        assert_eq!(layouts.selection(&cxt), vec![nodes[0], nodes[2]]);
+        assert!(!layouts.element(label).unwrap().is_selected());
+        layouts.press_selection(&mut cxt, Vec2::new(25., 25.), SelectMode::Toggle);
+        assert_eq!(layouts.selection(&cxt), vec![nodes[2]]);
+        assert_eq!(cxt.selection_current, layouts.element(nodes[2]).unwrap().id());
+        assert_eq!(*changes.lock().unwrap(), 3);
+
+        // a band from an empty spot selects what it touches
+        assert!(layouts.press_selection(&mut cxt, Vec2::new(125., 200.), SelectMode::Replace));
+        assert!(layouts.selection(&cxt).is_empty());
+        layouts.drag_selection(&mut cxt, Vec2::new(75., 25.));
+        assert_eq!(layouts.selection(&cxt), vec![nodes[1], nodes[2]]);
+        layouts.drag_selection(&mut cxt, Vec2::new(110., 25.));
+        assert_eq!(layouts.selection(&cxt), vec![nodes[2]]);
+        layouts.end_selection();
+        assert!(!layouts.is_rubber_banding());
+
+        layouts.press_selection(&mut cxt, Vec2::new(25., 200.), SelectMode::Toggle);
+        layouts.drag_selection(&mut cxt, Vec2::new(75., 25.));
+        assert_eq!(layouts.selection(&cxt), vec![nodes[2], nodes[0], nodes[1]]);
+        layouts.end_selection();
+    }
+
+    #[test]
+    fn test_group_drag() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let node = || element().size(Vec2::new(50., 50.)).shrink(0.).selectable(true).drag_enable(true);
+        let a = layouts.push_element_with_id(node(), content);
+        let b = layouts.push_element_with_id(node(), content);
+        let c = layouts.push_element_with_id(element().size(Vec2::new(50., 50.)).shrink(0.), content);
+        let origin = Vec3::new(-400., 300., 0.);
+        cxt.mouse_position = Vec2::new(25., 25.);
+        layouts.update(&mut cxt, origin);
+        layouts.set_selection(&mut cxt, vec![a, b]);
+        let offset = |layouts: &UILayouts, node| layouts.element(node).unwrap().anchor_offset;
+        let drag_to = |layouts: &mut UILayouts, cxt: &mut RwLockWriteGuard<MemState>, delta: Vec2| {
+            cxt.drag_delta.1 = cxt.drag_delta.0 + delta;
+            layouts.update(cxt, origin);
+        };
+
+        layouts.update_input_state(UIMouseState::Pressed);
+        cxt.drag_delta = (Vec2::new(25., 25.), Vec2::new(25., 25.), Vec2::ZERO);
+        drag_to(&mut layouts, &mut cxt, Vec2::new(20., 30.));
+        assert!(layouts.is_group_dragging());
+        assert_eq!(offset(&layouts, a), Vec3::new(20., -30., 0.));
+        assert_eq!(offset(&layouts, b), Vec3::new(20., -30., 0.));
+        assert_eq!(layouts.element(a).unwrap().get_rect().center(), Vec2::new(-355., 245.));
+        assert_eq!(offset(&layouts, c), Vec3::ZERO);
+
+        layouts.set_snap_grid(Some(40.));
+        drag_to(&mut layouts, &mut cxt, Vec2::new(23., 18.));
+        assert_eq!(offset(&layouts, b), Vec3::new(40., 0., 0.));
+
+        let escape = KeyboardInput { key_code: KeyCode::Escape, logical_key: Key::Escape, state: ButtonState::Pressed, window: Entity::PLACEHOLDER };
+        cxt.user_input = UIMouseState::Pressed;
+        layouts.key_input(&mut cxt, &escape, &ButtonInput::default());
+        assert_eq!(cxt.user_input, UIMouseState::Pressed);
+        assert!(!layouts.is_group_dragging());
+        assert_eq!(offset(&layouts, a), Vec3::ZERO);
+        assert_eq!(offset(&layouts, b), Vec3::ZERO);
+        drag_to(&mut layouts, &mut cxt, Vec2::new(60., 60.));
+        assert!(!layouts.is_group_dragging());
+        assert_eq!(offset(&layouts, a), Vec3::ZERO);
+
+        layouts.set_snap_grid(None);
+        layouts.set_alignment_guides(true);
+        layouts.update_input_state(UIMouseState::Release);
+        layouts.update(&mut cxt, origin);
+        layouts.update_input_state(UIMouseState::Pressed);
+        cxt.drag_delta = (Vec2::new(25., 25.), Vec2::new(25., 25.), Vec2::ZERO);
+        drag_to(&mut layouts, &mut cxt, Vec2::new(97., 100.));
+        assert_eq!(offset(&layouts, a), Vec3::new(100., -100., 0.));
+        let guides = &layouts.group_drag.as_ref().unwrap().guides;
+        assert_eq!(guides.len(), 1);
+        assert_eq!(guides[0].0.x, -300.);
+
+        layouts.update_input_state(UIMouseState::Release);
+        layouts.update(&mut cxt, origin);
+        assert!(!layouts.is_group_dragging());
+        assert_eq!(offset(&layouts, b), Vec3::new(100., -100., 0.));
+    }
+
+    #[test]
+    fn test_gestures() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let canvas_received = received.clone();
+        let canvas = layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(200., 200.))
+                .shrink(0.)
+                .on_gesture(move |_, gesture, _| canvas_received.lock().unwrap().push(("canvas", gesture))),
+            content,
+        );
+        let node_received = received.clone();
+        layouts.push_element_with_id(
+            element().size(Vec2::new(50., 50.)).shrink(0.).on_gesture(move |_, gesture, cxt| {
+                if let Gesture::Rotate(_) = gesture {
+                    node_received.lock().unwrap().push(("node", gesture));
+                    cxt.stop_propagation();
+                }
+            }),
+            canvas,
+        );
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+
+        let pinch = Gesture::Pinch(0.2);
+
+        assert!(layouts.gesture(&mut cxt, Vec2::new(25., 25.), pinch));
+        assert!(!layouts.gesture(&mut cxt, Vec2::new(500., 25.), pinch));
+        layouts.begin_gesture_drag(GestureDrag::Rotate, Vec2::new(25., 25.));
+        layouts.drag_gesture(&mut cxt, Vec2::new(-75., 40.));
+        layouts.end_gesture_drag();
+        layouts.drag_gesture(&mut cxt, Vec2::new(50., 40.));
+        layouts.begin_gesture_drag(GestureDrag::Pan, Vec2::new(150., 150.));
+        layouts.drag_gesture(&mut cxt, Vec2::new(160., 140.));
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![("canvas", Gesture::Pinch(0.2)), ("node", Gesture::Rotate(1.)), ("canvas", Gesture::Pan(Vec2::new(10., -10.)))]
+        );
+    }
+
+    #[test]
+    fn test_cursor() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let content = layouts.push_element(element());
+        let at = |left: f32| {
+            element()
+                .size(Vec2::new(100., 50.))
+                .position(Position::Absolute)
+                .inset(Length::Px(left), Length::Px(0.), Length::Auto, Length::Auto)
+        };
+        let button = layouts.push_element_with_id(at(0.).click(|_, _| {}), content);
+        layouts.push_element_with_id(element().size(Vec2::new(10., 50.)).shrink(0.).cursor(CursorIcon::EwResize), button);
+        layouts.push_element_with_id(at(100.).focusable(true).on_text_input(|_, _, _| {}), content);
+        layouts.push_element_with_id(at(200.).drag_enable(true), content);
+
+        let origin = Vec3::new(-400., 300., 0.);
+        let mut cursor_at = |x: f32| {
+            cxt.mouse_position = Vec2::new(x, 25.);
+            layouts.update(&mut cxt, origin);
+            layouts.cursor()
+        };
+        assert_eq!(cursor_at(50.), CursorIcon::Pointer);
+        assert_eq!(cursor_at(5.), CursorIcon::EwResize);
+        assert_eq!(cursor_at(150.), CursorIcon::Text);
+        assert_eq!(cursor_at(250.), CursorIcon::Move);
+        assert_eq!(cursor_at(500.), CursorIcon::Default);
+    }
+
+    #[test]
+    fn test_touch() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let (clicked, gestured) = (log.clone(), log.clone());
+        layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(200., 200.))
+                .shrink(0.)
+                .click(move |_, _| clicked.lock().unwrap().push("click".to_string()))
+                .on_gesture(move |_, gesture, _| gestured.lock().unwrap().push(format!("{gesture:?}"))),
+            content,
+        );
+        let origin = Vec3::new(-400., 300., 0.);
+        layouts.update(&mut cxt, origin);
+        let keys = ButtonInput::<KeyCode>::default();
+        let touch = |phase, id, x, y| TouchInput { phase, position: Vec2::new(x, y), window: Entity::PLACEHOLDER, force: None, id };
+
+        // a tap clicks like the left button
+        layouts.touch_input(&mut cxt, &touch(TouchPhase::Started, 0, 50., 50.), &keys);
+        layouts.touch_input(&mut cxt, &touch(TouchPhase::Ended, 0, 50., 50.), &keys);
+        layouts.update(&mut cxt, origin);
+        layouts.exc_action(&mut cxt);
+        assert_eq!(*log.lock().unwrap(), vec!["click"]);
+        log.lock().unwrap().clear();
+
+        // a second finger cancels the press and spreading both pans and pinches
+        layouts.touch_input(&mut cxt, &touch(TouchPhase::Started, 1, 80., 100.), &keys);
+        layouts.touch_input(&mut cxt, &touch(TouchPhase::Started, 2, 120., 100.), &keys);
+        layouts.touch_input(&mut cxt, &touch(TouchPhase::Moved, 2, 140., 100.), &keys);
+        layouts.touch_input(&mut cxt, &touch(TouchPhase::Ended, 1, 80., 100.), &keys);
+        layouts.touch_input(&mut cxt, &touch(TouchPhase::Ended, 2, 140., 100.), &keys);
+        layouts.update(&mut cxt, origin);
+        layouts.exc_action(&mut cxt);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![format!("{:?}", Gesture::Pan(Vec2::new(10., 0.))), format!("{:?}", Gesture::Pinch(0.5))]
+        );
+    }
+
+    #[test]
+    fn test_wheel_routing() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let canvas_received = received.clone();
+        let canvas = layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(200., 200.))
+                .shrink(0.)
+                .on_gesture(move |_, gesture, _| canvas_received.lock().unwrap().push(gesture)),
+            content,
+        );
+        let list = layouts.push_element_with_id(
+            element().size(Vec2::new(100., 100.)).shrink(0.).direction(FlexDirection::Column).scroll(ScrollAxes::Vertical),
+            canvas,
+        );
+        for _ in 0..5 {
+            layouts.push_element_with_id(element().size(Vec2::new(80., 50.)).shrink(0.), list);
+        }
+        let origin = Vec3::new(-400., 300., 0.);
+        layouts.update(&mut cxt, origin);
+        let wheel = |y| MouseWheel { unit: MouseScrollUnit::Pixel, x: 0., y, window: Entity::PLACEHOLDER };
+
+        // the list inside the canvas scrolls first, the canvas pans once it can't
+        layouts.wheel(&mut cxt, Vec2::new(50., 50.), &wheel(-1000.), false);
+        assert_eq!(layouts.element(list).unwrap().get_scroll_offset().y, 150.);
+        assert!(received.lock().unwrap().is_empty());
+        layouts.update(&mut cxt, origin);
+        layouts.wheel(&mut cxt, Vec2::new(50., 50.), &wheel(-10.), false);
+        layouts.wheel(&mut cxt, Vec2::new(150., 150.), &wheel(5.), false);
+        assert_eq!(*received.lock().unwrap(), vec![Gesture::Pan(Vec2::new(0., -10.)), Gesture::Pan(Vec2::new(0., 5.))]);
+    }
+
+    #[test]
+    fn test_wheel_direction() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let list = layouts.push_element_with_id(
+            element().size(Vec2::new(100., 100.)).shrink(0.).direction(FlexDirection::Column).scroll(ScrollAxes::Vertical),
+            content,
+        );
+        for _ in 0..5 {
+            layouts.push_element_with_id(element().size(Vec2::new(80., 50.)).shrink(0.), list);
+        }
+        let canvas_received = received.clone();
+        layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(100., 100.))
+                .shrink(0.)
+                .on_gesture(move |_, gesture, _| canvas_received.lock().unwrap().push(gesture)),
+            content,
+        );
+        layouts.update(&mut cxt, Vec3::new(-400., 300., 0.));
+
+        // the same upward swipe moves the list content and the canvas content up by 30 pixels
+        let swipe = MouseWheel { unit: MouseScrollUnit::Pixel, x: 0., y: -30., window: Entity::PLACEHOLDER };
+        layouts.wheel(&mut cxt, Vec2::new(50., 50.), &swipe, false);
+        layouts.wheel(&mut cxt, Vec2::new(150., 50.), &swipe, false);
+        let scrolled = layouts.element(list).unwrap().get_scroll_offset();
+        assert_eq!(scrolled, Vec2::new(0., 30.));
+        assert_eq!(*received.lock().unwrap(), vec![Gesture::Pan(-scrolled)]);
+    }
+
+    #[test]
+    fn test_context_menu() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let picked = Arc::new(Mutex::new(Vec::new()));
+        let content = layouts.push_element(element());
+        let (right, item) = (picked.clone(), picked.clone());
+        layouts.push_element_with_id(
+            element()
+                .size(Vec2::new(100., 50.))
+                .shrink(0.)
+                .on_right_click(move |_, _| right.lock().unwrap().push("right"))
+                .on_context_menu(move || {
+                    let item = item.clone();
+                    element().title("menu").size(Vec2::new(80., 20.)).click(move |_, _| item.lock().unwrap().push("item"))
+                }),
+            content,
+        );
+        let origin = Vec3::new(-400., 300., 0.);
+        cxt.mouse_position = Vec2::new(50., 25.);
+        layouts.update(&mut cxt, origin);
+        layouts.press_button(MouseButton::Right, cxt.mouse_position);
+        layouts.release_button(&mut cxt, MouseButton::Right, Vec2::new(50., 25.));
+        let menu = layouts.context_menu().unwrap();
+        assert_eq!(layouts.element(menu).unwrap().get_title(), "menu");
+
+        cxt.mouse_position = Vec2::new(60., 30.);
+        layouts.update(&mut cxt, origin);
+        layouts.press_button(MouseButton::Left, cxt.mouse_position);
+        layouts.update_input_state(UIMouseState::Pressed);
+        layouts.update_input_state(UIMouseState::Release);
+        layouts.exc_action(&mut cxt);
+        assert_eq!(*picked.lock().unwrap(), vec!["right", "item"]);
+        assert!(layouts.context_menu().is_none());
+        assert!(layouts.element(menu).is_none());
+
+        layouts.press_button(MouseButton::Right, Vec2::new(50., 25.));
+        layouts.release_button(&mut cxt, MouseButton::Right, Vec2::new(50., 25.));
+        layouts.press_button(MouseButton::Left, Vec2::new(300., 300.));
+        assert!(layouts.context_menu().is_none());
+    }
+
+    #[test]
+    fn test_middle_drag_pan() {
+        let state = RwLock::new(MemState::default());
+        let mut cxt = state.write().unwrap();
+        let mut layouts = UILayouts::new();
+        let clicks = Arc::new(Mutex::new(0));
+        let content = layouts.push_element(element());
+        let rows: Vec<Element> = (0..10).map(|_| element().size(Vec2::new(80., 40.)).shrink(0.)).collect();
+        let clicked = clicks.clone();
+        let canvas = element()
+            .size(Vec2::new(100., 100.))
+            .shrink(0.)
+            .direction(FlexDirection::Column)
+            .scroll(ScrollAxes::Vertical)
+            .on_middle_click(move |_, _| *clicked.lock().unwrap() += 1)
+            .on_middle_drag(|element, cxt| {
+                element.scroll_by(-cxt.pan_delta);
+            });
+        let canvas = layouts.push_element_with_id(canvas, content);
+        for row in rows {
+            layouts.push_element_with_id(row, canvas);
+        }
+        let origin = Vec3::new(-400., 300., 0.);
+        cxt.mouse_position = Vec2::new(50., 80.);
+        layouts.update(&mut cxt, origin);
+
+        layouts.press_button(MouseButton::Middle, Vec2::new(50., 80.));
+        layouts.drag_buttons(&mut cxt, Vec2::new(50., 78.));
+        layouts.release_button(&mut cxt, MouseButton::Middle, Vec2::new(50., 78.));
+        assert_eq!(*clicks.lock().unwrap(), 1);
+        assert_eq!(layouts.element(canvas).unwrap().get_scroll_offset(), Vec2::ZERO);
+
+        layouts.press_button(MouseButton::Middle, Vec2::new(50., 80.));
+        layouts.drag_buttons(&mut cxt, Vec2::new(50., 60.));
+        layouts.drag_buttons(&mut cxt, Vec2::new(50., 50.));
+        layouts.release_button(&mut cxt, MouseButton::Middle, Vec2::new(50., 50.));
+        assert_eq!(*clicks.lock().unwrap(), 1);
+        assert_eq!(layouts.element(canvas).unwrap().get_scroll_offset(), Vec2::new(0., 30.));
+    }
+}
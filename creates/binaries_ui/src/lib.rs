@@ -5,6 +5,13 @@ pub mod utils;
 pub mod layout;
 pub mod shape;
 pub mod text;
+pub mod inspector;
+pub mod shortcut;
+pub mod drag_drop;
+pub mod selection;
+pub mod gesture;
+mod overlay;
+mod touch;
 mod storage;
 mod context;
 
@@ -22,7 +29,7 @@ pub struct Config<'w,'s> {
    pub default_font: ResMut<'w,DefaultFont>,
    pub meshes: ResMut<'w,Assets<Mesh>>,
    pub materials: ResMut<'w,Assets<ColorMaterial>>,
-   pub window: Query<'w,'s,&'static Window, With<PrimaryWindow>>,
+   pub window: Query<'w,'s,&'static mut Window, With<PrimaryWindow>>,
    pub context: ResMut<'w,Context>,
 }
 pub struct UIPlugin;
@@ -0,0 +1,91 @@
+use bevy::{color::{palettes::css::{DEEP_SKY_BLUE, DODGER_BLUE, LIMEGREEN, MAGENTA, ORANGE}, Alpha}, math::{Rect, Vec2, Vec3, Vec4}};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::{LinePainter, RectPainter}};
+
+use crate::components::element::Element;
+
+/// Opacity of the drag ghost.
+const GHOST_ALPHA: f32 = 0.5;
+
+/// Outline the layout rect of `element` and shade its margin and padding, like browser devtools.
+/// The overlays are drawn at depth `z`, above every element.
+pub(crate) fn draw_bounds(painter: &mut ShapePainter, element: &Element, z: f32) {
+    let rect = element.get_rect();
+    let (center, size) = (rect.center(), rect.size());
+    let margin = element.get_margin();
+    let padding = element.get_padding();
+
+    painter.corner_radii = Vec4::ZERO;
+    painter.set_color(ORANGE.with_alpha(0.35));
+    let height = size.y + margin.y + margin.w;
+    let middle = center.y + (margin.y - margin.w) / 2.;
+    strip(painter, Vec2::new(rect.min.x - margin.x / 2., middle).extend(z), Vec2::new(margin.x, height));
+    strip(painter, Vec2::new(rect.max.x + margin.z / 2., middle).extend(z), Vec2::new(margin.z, height));
+    strip(painter, Vec2::new(center.x, rect.max.y + margin.y / 2.).extend(z), Vec2::new(size.x, margin.y));
+    strip(painter, Vec2::new(center.x, rect.min.y - margin.w / 2.).extend(z), Vec2::new(size.x, margin.w));
+
+    painter.set_color(LIMEGREEN.with_alpha(0.35));
+    let width = size.x - padding.x - padding.z;
+    let inner = center.x + (padding.x - padding.z) / 2.;
+    strip(painter, Vec2::new(rect.min.x + padding.x / 2., center.y).extend(z), Vec2::new(padding.x, size.y));
+    strip(painter, Vec2::new(rect.max.x - padding.z / 2., center.y).extend(z), Vec2::new(padding.z, size.y));
+    strip(painter, Vec2::new(inner, rect.max.y - padding.y / 2.).extend(z), Vec2::new(width, padding.y));
+    strip(painter, Vec2::new(inner, rect.min.y + padding.w / 2.).extend(z), Vec2::new(width, padding.w));
+
+    painter.set_color(DEEP_SKY_BLUE);
+    painter.hollow = true;
+    painter.thickness = 1.;
+    painter.set_translation(center.extend(z));
+    painter.rect(size);
+    painter.hollow = false;
+    painter.set_translation(Vec3::ZERO);
+}
+
+/// Translucent box of a rubber band selection.
+pub(crate) fn draw_rubber_band(painter: &mut ShapePainter, rect: Rect, z: f32) {
+    painter.corner_radii = Vec4::ZERO;
+    painter.set_translation(rect.center().extend(z));
+    painter.set_color(DODGER_BLUE.with_alpha(0.15));
+    painter.rect(rect.size());
+    painter.set_color(DODGER_BLUE);
+    painter.hollow = true;
+    painter.thickness = 1.;
+    painter.rect(rect.size());
+    painter.hollow = false;
+    painter.set_translation(Vec3::ZERO);
+}
+
+/// Alignment guides of a group drag.
+pub(crate) fn draw_guides(painter: &mut ShapePainter, guides: &[(Vec2, Vec2)], z: f32) {
+    painter.set_color(MAGENTA);
+    painter.thickness = 1.;
+    painter.set_translation(Vec3::ZERO);
+    for (start, end) in guides {
+        painter.line(start.extend(z), end.extend(z));
+    }
+}
+
+/// Title and computed size shown above each box.
+pub(crate) fn bounds_label(element: &Element) -> String {
+    let size = element.get_size();
+    format!("{} {:.0}x{:.0}", element.get_title(), size.x, size.y)
+}
+
+/// Where the label of `element` is drawn, centered on the top edge of its rect at depth `z`.
+pub(crate) fn label_anchor(element: &Element, z: f32) -> Vec3 {
+    let rect = element.get_rect();
+    Vec3::new(rect.center().x, rect.max.y - 6., z)
+}
+
+fn strip(painter: &mut ShapePainter, center: Vec3, size: Vec2) {
+    if size.x <= 0. || size.y <= 0. {
+        return;
+    }
+    painter.set_translation(center);
+    painter.rect(size);
+}
+
+/// Translucent copy of a dragged element under the cursor at depth `z`, above everything else.
+pub(crate) fn draw_ghost(painter: &mut ShapePainter, element: &Element, center: Vec2, z: f32) {
+    element.draw_ghost(painter, center, z, GHOST_ALPHA);
+    painter.set_translation(Vec3::ZERO);
+}
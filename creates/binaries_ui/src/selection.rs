@@ -0,0 +1,105 @@
+use bevy::{input::{keyboard::KeyCode, ButtonInput}, math::{Rect, Vec2, Vec3}};
+use taffy::NodeId;
+
+/// How a click or a rubber band changes the selection.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelectMode {
+    /// Select only the clicked or enclosed elements.
+    Replace,
+    /// Add them to the selection, with Shift.
+    Extend,
+    /// Flip their selected state, with Ctrl or the command key.
+    Toggle,
+}
+
+impl SelectMode {
+    /// Mode given by the modifiers held in `keys`.
+    pub fn from_keys(keys: &ButtonInput<KeyCode>) -> Self {
+        if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]) {
+            SelectMode::Toggle
+        } else if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            SelectMode::Extend
+        } else {
+            SelectMode::Replace
+        }
+    }
+
+    /// Selection made of `base` changed by the elements in `hits`.
+    pub(crate) fn apply(&self, base: &[NodeId], hits: &[NodeId]) -> Vec<NodeId> {
+        match self {
+            SelectMode::Replace => hits.to_vec(),
+            SelectMode::Extend => {
+                let mut selection = base.to_vec();
+                selection.extend(hits.iter().filter(|node| !base.contains(node)));
+                selection
+            }
+            SelectMode::Toggle => {
+                let mut selection: Vec<NodeId> = base.iter().copied().filter(|node| !hits.contains(node)).collect();
+                selection.extend(hits.iter().filter(|node| !base.contains(node)));
+                selection
+            }
+        }
+    }
+}
+
+/// Box selection dragged from an empty spot, in painter space.
+pub(crate) struct RubberBand {
+    pub(crate) start: Vec2,
+    pub(crate) end: Vec2,
+    pub(crate) mode: SelectMode,
+    /// Selection when the band started, the band changes it with `mode`.
+    pub(crate) base: Vec<NodeId>,
+}
+
+impl RubberBand {
+    pub(crate) fn rect(&self) -> Rect {
+        Rect::from_corners(self.start, self.end)
+    }
+}
+
+/// Selected elements dragged together with `leader`.
+pub(crate) struct GroupDrag {
+    pub(crate) leader: NodeId,
+    /// Members with their `anchor_offset` when the drag started.
+    pub(crate) members: Vec<(NodeId, Vec3)>,
+    /// Rect of the leader when the drag started, in painter space.
+    pub(crate) origin: Rect,
+    /// Alignment guides shown for the current position, as line segments in painter space.
+    pub(crate) guides: Vec<(Vec2, Vec2)>,
+}
+
+/// Distance under which a dragged edge or center aligns with a neighbor.
+pub(crate) const ALIGN_THRESHOLD: f32 = 5.;
+
+/// Shift along one axis aligning an edge or the center of `moved` with those of `other`,
+/// with the aligned coordinate.
+pub(crate) fn align(moved: [f32; 3], other: [f32; 3]) -> Option<(f32, f32)> {
+    moved
+        .iter()
+        .flat_map(|a| other.iter().map(move |b| (b - a, *b)))
+        .filter(|(shift, _)| shift.abs() <= ALIGN_THRESHOLD)
+        .min_by(|a, b| a.0.abs().total_cmp(&b.0.abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_mode() {
+        let [a, b, c] = [1u64, 2, 3].map(NodeId::from);
+        assert_eq!(SelectMode::Replace.apply(&[a, b], &[c]), vec![c]);
+        assert_eq!(SelectMode::Extend.apply(&[a, b], &[b, c]), vec![a, b, c]);
+        assert_eq!(SelectMode::Toggle.apply(&[a, b], &[b, c]), vec![a, c]);
+        assert_eq!(SelectMode::Toggle.apply(&[], &[]), vec![]);
+    }
+
+    #[test]
+    fn test_align() {
+        // left, center and right of the moved and the other rect
+        assert_eq!(align([0., 50., 100.], [103., 150., 200.]), Some((3., 103.)));
+        assert_eq!(align([0., 50., 100.], [46., 73., 99.]), Some((-1., 99.)));
+        assert_eq!(align([0., 50., 100.], [-4., 150., 200.]), Some((-4., -4.)));
+        assert_eq!(align([0., 50., 100.], [106., 156., 206.]), None);
+    }
+}
@@ -1,9 +1,9 @@
-use std::f32::consts::PI;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
 
-use bevy::{color::Color, math::{Vec2, Vec3, Vec4}, prelude::{Commands, Transform}, sprite::{MaterialMesh2dBundle, Mesh2dHandle}};
-use bevy_vector_shapes::{prelude::ShapePainter, shapes::{DiscPainter, LinePainter, RectPainter, RegularPolygonPainter}};
+use bevy::{color::Color, math::{Rect, Vec2, Vec3, Vec4}, prelude::{Commands, Mesh, Transform, Visibility}, render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages}, sprite::{MaterialMesh2dBundle, Mesh2dHandle}};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::{DiscPainter, LinePainter, RectPainter, RegularPolygonPainter, TrianglePainter}};
 
-use crate::Config;
+use crate::{components::element::Clip, Config};
 
 pub trait ShapeTrait: Send + Sync + 'static {
     fn draw(&self, painter: &mut ShapePainter);
@@ -17,6 +17,105 @@ pub trait ShapeTrait: Send + Sync + 'static {
         let _ = cursor;
         false
     }
+    fn set_text(&mut self, content: &str) {
+        let _ = content;
+    }
+    /// Draw the part inside `clip` of a shape centered on `center`, both in painter space.
+    /// Shapes that can't be cut are drawn whole while any part of their size is visible,
+    /// or while their center is when they have no size.
+    fn draw_clipped(&self, painter: &mut ShapePainter, center: Vec3, clip: Clip) {
+        let visible = match self.get_size() {
+            Some(size) => clip.overlaps(Rect::from_center_size(center.truncate(), size)),
+            None => clip.contains(center.truncate()),
+        };
+        if visible {
+            self.draw(painter);
+        }
+    }
+    /// Hide the entities spawned in `update`, used when the shape is scrolled out of view.
+    fn set_visible(&mut self, visible: bool) {
+        let _ = visible;
+    }
+    /// Cut the entities spawned in `update` to `clip`, in painter space, `None` shows them whole.
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        let _ = clip;
+    }
+    /// Release the entities spawned in `update` once the element is removed.
+    fn despawn(&mut self, commands: &mut Commands) {
+        let _ = commands;
+    }
+}
+
+/// Segments of the polygon standing in for a circle once it is cut.
+const CIRCLE_SEGMENTS: usize = 64;
+
+/// Draw the part of a `size` rect centered on `center` inside `clip`, its corners following the clip.
+fn draw_rect_clipped(painter: &mut ShapePainter, center: Vec3, size: Vec2, round: Vec4, clip: Clip) {
+    let rect = clip.rect().intersect(Rect::from_center_size(center.truncate(), size));
+    if rect.is_empty() {
+        return;
+    }
+    painter.corner_radii = round.max(clip.fit(rect));
+    painter.set_translation(rect.center().extend(center.z));
+    painter.rect(rect.size());
+    painter.set_translation(center);
+}
+
+/// Corners of a regular polygon of `radius` around `center`, the first one above it before `rotation`.
+fn polygon_outline(center: Vec2, radius: f32, sides: usize, rotation: f32) -> Vec<Vec2> {
+    (0..sides)
+        .map(|side| center + radius * Vec2::from_angle(FRAC_PI_2 + rotation + side as f32 * TAU / sides as f32))
+        .collect()
+}
+
+/// Cut the convex `polygon` to `rect` one side at a time, empty when they don't overlap.
+pub(crate) fn clip_polygon(polygon: &[Vec2], rect: Rect) -> Vec<Vec2> {
+    let sides = [(0, rect.min.x, 1.), (0, rect.max.x, -1.), (1, rect.min.y, 1.), (1, rect.max.y, -1.)];
+    let mut points = polygon.to_vec();
+    for (axis, bound, sign) in sides {
+        let inside = |point: Vec2| (point[axis] - bound) * sign >= 0.;
+        let mut cut = Vec::with_capacity(points.len() + 1);
+        for (index, &point) in points.iter().enumerate() {
+            let previous = points[(index + points.len() - 1) % points.len()];
+            if inside(point) != inside(previous) {
+                cut.push(previous.lerp(point, (bound - previous[axis]) / (point[axis] - previous[axis])));
+            }
+            if inside(point) {
+                cut.push(point);
+            }
+        }
+        points = cut;
+    }
+    points
+}
+
+/// Fill the part of the convex `outline` inside `clip` with triangles around `center`, both in painter space.
+/// Returns false without drawing when the outline fits in the clip and can be drawn whole instead.
+fn fill_clipped(painter: &mut ShapePainter, center: Vec3, outline: &[Vec2], clip: Clip) -> bool {
+    if outline.iter().all(|point| clip.rect().contains(*point)) {
+        return false;
+    }
+    let cut = clip_polygon(outline, clip.rect());
+    let origin = center.truncate();
+    for index in 2..cut.len() {
+        painter.triangle(cut[0] - origin, cut[index - 1] - origin, cut[index] - origin);
+    }
+    true
+}
+
+/// Mesh of the parts of `triangles` inside `rect`.
+fn clipped_mesh(triangles: &[Vec<Vec2>], rect: Rect) -> Mesh {
+    let mut positions = Vec::new();
+    for triangle in triangles {
+        let cut = clip_polygon(triangle, rect);
+        for index in 2..cut.len() {
+            positions.extend([cut[0], cut[index - 1], cut[index]].map(|point| [point.x, point.y, 0.]));
+        }
+    }
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.compute_flat_normals();
+    mesh
 }
 
 #[derive(Clone,Debug)]
@@ -52,10 +151,18 @@ impl ShapeTrait for Rectangle {
     fn set_size(&mut self, size: Vec2) {
         self.size = size;
     }
+
+    fn get_size(&self) -> Option<Vec2> {
+        Some(self.size)
+    }
     
     fn set_round(&mut self,round:Vec4) {
         self.round = round;
     }
+
+    fn draw_clipped(&self, painter: &mut ShapePainter, center: Vec3, clip: Clip) {
+        draw_rect_clipped(painter, center, self.size, self.round, clip);
+    }
     
     fn update(&mut self,config: &mut Config, commands: &mut Commands, offset: Vec3){
         let _ = offset;
@@ -104,6 +211,13 @@ impl ShapeTrait for Ngon {
         self.round = round;
     }
 
+    fn draw_clipped(&self, painter: &mut ShapePainter, center: Vec3, clip: Clip) {
+        let outline = polygon_outline(center.truncate(), self.radius, self.sides.max(3.) as usize, self.rotation / 180. * PI);
+        if !fill_clipped(painter, center, &outline, clip) {
+            self.draw(painter);
+        }
+    }
+
     fn update(&mut self,config: &mut Config, commands: &mut Commands, offset: Vec3){
         let _ = offset;
         let _ = commands;
@@ -129,7 +243,6 @@ impl Default for Circle {
 
 impl ShapeTrait for Circle {
     fn draw(&self, painter: &mut ShapePainter) {
-        painter.translate(Vec3::Z);
         painter.circle(self.radius);
     }
     
@@ -141,6 +254,13 @@ impl ShapeTrait for Circle {
         self.round = round;
     }
 
+    fn draw_clipped(&self, painter: &mut ShapePainter, center: Vec3, clip: Clip) {
+        let outline = polygon_outline(center.truncate(), self.radius, CIRCLE_SEGMENTS, 0.);
+        if !fill_clipped(painter, center, &outline, clip) {
+            self.draw(painter);
+        }
+    }
+
     fn update(&mut self,config: &mut Config, commands: &mut Commands, offset: Vec3){
         let _ = offset;
         let _ = commands;
@@ -217,6 +337,13 @@ pub struct Text {
     pub mesh: Vec<Vec<Vec2>>,
     pub entity: Option<bevy::ecs::entity::Entity>,
     pub screen_position: Vec2,
+    pub outdated: bool,
+    pub visible: bool,
+    /// Clip in painter space set by the layout.
+    pub clip: Option<Rect>,
+    /// Clip in mesh space the current mesh was cut to.
+    cut: Option<Rect>,
+    full_mesh: Option<Mesh2dHandle>,
 }
 
 impl Default for Text {
@@ -230,6 +357,11 @@ impl Default for Text {
             content_size: Vec2::ZERO,
             entity: None,
             screen_position: Vec2::ZERO,
+            outdated: false,
+            visible: true,
+            clip: None,
+            cut: None,
+            full_mesh: None,
         }
     }
 }
@@ -241,6 +373,26 @@ impl Text {
             ..Default::default()
         }
     }
+
+    /// Give `entity` the part of the glyphs inside the clip, its mesh space starts at `origin` in painter space.
+    fn update_cut(&mut self, config: &mut Config, commands: &mut Commands, entity: bevy::ecs::entity::Entity, origin: Vec2) {
+        let bounds = Rect::from_corners(origin, origin + Vec2::new(self.content_size.x, -self.content_size.y));
+        let cut = self.clip
+            .filter(|clip| clip.intersect(bounds) != bounds)
+            .map(|clip| Rect { min: clip.min - origin, max: clip.max - origin });
+        if cut == self.cut {
+            return;
+        }
+        self.cut = cut;
+        let mesh = match cut {
+            Some(rect) => Mesh2dHandle(config.meshes.add(clipped_mesh(&self.mesh, rect))),
+            None => match self.full_mesh.clone() {
+                Some(mesh) => mesh,
+                None => return,
+            },
+        };
+        commands.entity(entity).insert(mesh);
+    }
 }
 
 impl ShapeTrait for Text {
@@ -265,22 +417,61 @@ impl ShapeTrait for Text {
         let _ = round;
     }
 
+    fn set_text(&mut self, content: &str) {
+        if self.content != content {
+            self.content = content.to_string();
+            self.outdated = true;
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+    }
+
+    fn draw_clipped(&self, painter: &mut ShapePainter, center: Vec3, clip: Clip) {
+        draw_rect_clipped(painter, center, self.size, Vec4::ZERO, clip);
+    }
+
+    fn despawn(&mut self, commands: &mut Commands) {
+        if let Some(entity_id) = self.entity.take() {
+            commands.entity(entity_id).despawn();
+        }
+    }
+
     fn update(&mut self,config: &mut Config, commands: &mut Commands, offset: Vec3){
+        if self.outdated {
+            self.despawn(commands);
+            self.outdated = false;
+        }
         match self.entity {
             Some(entity_id) => {
-                commands.entity(entity_id).insert(
-                    Transform::from_xyz(offset.x -self.content_size.x * 0.5,offset.y + self.content_size.y * 0.5,1.)
-                );
+                let visibility = match self.visible {
+                    true => Visibility::Inherited,
+                    false => Visibility::Hidden,
+                };
+                let origin = Vec3::new(offset.x -self.content_size.x * 0.5,offset.y + self.content_size.y * 0.5,offset.z);
+                commands.entity(entity_id).insert((
+                    Transform::from_translation(origin),
+                    visibility,
+                ));
+                self.update_cut(config, commands, entity_id, origin.truncate());
             },
             None => {
                 let text =  crate::text::TextShape::builder().size(self.font_size).build(config.default_font.font(), &self.content);
                 self.content_size = Vec2::new(text.witdh, text.height);
                 let text_meshes = Mesh2dHandle(config.meshes.add(text.bevy_mesh()));
+                self.mesh = text.mesh();
+                self.full_mesh = Some(text_meshes.clone());
+                self.cut = None;
                 let origin =  offset;
                 self.entity = Some(commands.spawn((
                     MaterialMesh2dBundle {
                         mesh: text_meshes.clone(),
-                        transform: Transform::from_xyz(origin.x -self.content_size.x * 0.5,origin.y + self.content_size.y * 0.5,1.),
+                        transform: Transform::from_xyz(origin.x -self.content_size.x * 0.5,origin.y + self.content_size.y * 0.5,origin.z),
                         material: config.materials.add(Color::srgb(1., 1., 1.)),    
                         ..Default::default()
                     },
@@ -0,0 +1,103 @@
+use std::sync::{Arc, RwLockWriteGuard};
+
+use bevy::input::{keyboard::KeyCode, ButtonInput};
+use taffy::NodeId;
+
+use crate::{context::MemState, layout::UILayouts};
+
+pub(crate) type ShortcutAction = Arc<dyn Fn(&mut UILayouts, &mut RwLockWriteGuard<MemState>) + Send + Sync + 'static>;
+
+/// A key with the modifiers that must be held, `ctrl` also matches the command key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Shortcut {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Shortcut {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Shortcut made by pressing `key` with the modifiers currently held in `keys`.
+    pub fn pressed(key: KeyCode, keys: &ButtonInput<KeyCode>) -> Self {
+        Self {
+            key,
+            ctrl: keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]),
+            shift: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            alt: keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ShortcutId(u64);
+
+struct Binding {
+    id: ShortcutId,
+    shortcut: Shortcut,
+    scope: Option<NodeId>,
+    action: ShortcutAction,
+}
+
+/// Shortcuts of the application, global or active while the focus is inside a subtree.
+#[derive(Default)]
+pub(crate) struct Shortcuts {
+    bindings: Vec<Binding>,
+    next_id: u64,
+}
+
+impl Shortcuts {
+    pub(crate) fn add(&mut self, shortcut: Shortcut, scope: Option<NodeId>, action: ShortcutAction) -> ShortcutId {
+        let id = ShortcutId(self.next_id);
+        self.next_id += 1;
+        self.bindings.push(Binding { id, shortcut, scope, action });
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: ShortcutId) {
+        self.bindings.retain(|binding| binding.id != id);
+    }
+
+    /// Drop the shortcuts scoped to a removed subtree.
+    pub(crate) fn remove_scopes(&mut self, removed: impl Fn(NodeId) -> bool) {
+        self.bindings.retain(|binding| !binding.scope.is_some_and(|scope| removed(scope)));
+    }
+
+    /// Action of `shortcut` for a focus whose ancestors, from the focused node up, are `path`.
+    /// The innermost scope wins, global shortcuts come last.
+    pub(crate) fn find(&self, shortcut: Shortcut, path: &[NodeId]) -> Option<ShortcutAction> {
+        let depth = |binding: &Binding| match binding.scope {
+            Some(scope) => path.iter().position(|node| *node == scope),
+            None => Some(path.len()),
+        };
+        self.bindings
+            .iter()
+            .filter(|binding| binding.shortcut == shortcut)
+            .filter_map(|binding| depth(binding).map(|depth| (depth, binding)))
+            .min_by_key(|(depth, _)| *depth)
+            .map(|(_, binding)| binding.action.clone())
+    }
+}
@@ -0,0 +1,71 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::math::Vec2;
+
+/// Fingers on the screen in the order they touched it. The first one drives the pointer
+/// until a second one turns the touch into pan, pinch and rotate gestures.
+#[derive(Default)]
+pub(crate) struct Touches {
+    pub(crate) points: Vec<(u64, Vec2)>,
+    /// Finger driving the pointer.
+    pub(crate) primary: Option<u64>,
+    /// Shape of the first two fingers when the last gestures were sent.
+    pub(crate) pair: Option<TouchPair>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct TouchPair {
+    pub(crate) center: Vec2,
+    pub(crate) distance: f32,
+    /// Angle of the line between the fingers, counterclockwise on screen.
+    pub(crate) angle: f32,
+}
+
+impl Touches {
+    pub(crate) fn pair_shape(&self) -> Option<TouchPair> {
+        let [(_, a), (_, b), ..] = self.points.as_slice() else {
+            return None;
+        };
+        let line = *b - *a;
+        Some(TouchPair {
+            center: (*a + *b) / 2.,
+            distance: line.length(),
+            // window coordinates go down
+            angle: (-line.y).atan2(line.x),
+        })
+    }
+}
+
+impl TouchPair {
+    /// Rotation from `self` to `other`, the shortest way round.
+    pub(crate) fn rotation_to(&self, other: &TouchPair) -> f32 {
+        (other.angle - self.angle + PI).rem_euclid(TAU) - PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_shape() {
+        let mut touches = Touches::default();
+        touches.points.push((1, Vec2::new(10., 10.)));
+        assert_eq!(touches.pair_shape(), None);
+        touches.points.push((2, Vec2::new(10., 0.)));
+        let pair = touches.pair_shape().unwrap();
+        assert_eq!(pair.center, Vec2::new(10., 5.));
+        assert_eq!(pair.distance, 10.);
+        // the second finger is above the first on screen
+        assert_eq!(pair.angle, PI / 2.);
+    }
+
+    #[test]
+    fn test_rotation_to() {
+        let pair = |angle| TouchPair { center: Vec2::ZERO, distance: 1., angle };
+        assert!((pair(0.1).rotation_to(&pair(0.3)) - 0.2).abs() < 1e-6);
+        // crossing from +PI to -PI is a small counterclockwise turn
+        assert!((pair(PI - 0.1).rotation_to(&pair(-PI + 0.1)) - 0.2).abs() < 1e-6);
+        assert!((pair(-PI + 0.1).rotation_to(&pair(PI - 0.1)) + 0.2).abs() < 1e-6);
+    }
+}